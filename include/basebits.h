@@ -0,0 +1,39 @@
+/* C interface to basebits, built with the `ffi` feature. See the README for how to build the
+ * shared and static libraries. Sequences are passed around as their raw `code` and `nbits`. */
+#ifndef BASEBITS_H
+#define BASEBITS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The longest sequence that can be encoded */
+#define BASEBITS_MAX_BASES 21
+
+/* Encoding succeeded */
+#define BASEBITS_OK 0
+/* A required pointer was null */
+#define BASEBITS_ERR_NULL (-1)
+/* The sequence is longer than BASEBITS_MAX_BASES */
+#define BASEBITS_ERR_TOO_LONG (-2)
+
+/* Encode `len` bytes starting at `ptr` into `out_code` and `out_nbits`. Returns BASEBITS_OK or
+ * one of the BASEBITS_ERR_* codes, in which case the outputs are left untouched. */
+int32_t basebits_encode(const uint8_t *ptr, size_t len, uint64_t *out_code, uint64_t *out_nbits);
+
+/* n-any distance between two encodings of sequences of the same length */
+uint32_t basebits_dist_nany(uint64_t alpha_code, uint64_t alpha_nbits, uint64_t beta_code,
+                            uint64_t beta_nbits);
+
+/* n-none distance between two encodings of sequences of the same length */
+uint32_t basebits_dist_none(uint64_t alpha_code, uint64_t alpha_nbits, uint64_t beta_code,
+                            uint64_t beta_nbits);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BASEBITS_H */
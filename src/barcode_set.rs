@@ -0,0 +1,106 @@
+//! A validated whitelist of barcodes that all share one length.
+use crate::{nearest_nany, BaseBits, BaseBitsError};
+use alloc::vec::Vec;
+
+/// A whitelist of barcodes, all checked to be the same length when the set is built. Lookups
+/// can then compare full words without worrying about mismatched lengths.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct BarcodeSet {
+    barcodes: Vec<BaseBits>,
+    seq_len: usize,
+}
+
+impl BarcodeSet {
+    /// Encode every sequence in `seqs` into a new set. Errors if a sequence is too long to
+    /// encode, or with `LengthMismatch` if it isn't the same length as the first one.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_iter<I, S>(seqs: I) -> Result<BarcodeSet, BaseBitsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[u8]>,
+    {
+        let mut barcodes: Vec<BaseBits> = Vec::new();
+        for seq in seqs {
+            let bb = BaseBits::new(seq.as_ref())?;
+            if let Some(first) = barcodes.first() {
+                if bb.len != first.len {
+                    return Err(BaseBitsError::LengthMismatch {
+                        expected: first.len,
+                        found: bb.len,
+                    });
+                }
+            }
+            barcodes.push(bb);
+        }
+        let seq_len = barcodes.first().map_or(0, |bb| bb.len);
+        Ok(BarcodeSet { barcodes, seq_len })
+    }
+
+    /// The number of barcodes in the set
+    pub fn len(&self) -> usize {
+        self.barcodes.len()
+    }
+
+    /// Check if the set has no barcodes
+    pub fn is_empty(&self) -> bool {
+        self.barcodes.is_empty()
+    }
+
+    /// The length shared by every barcode, 0 for an empty set
+    pub fn seq_len(&self) -> usize {
+        self.seq_len
+    }
+
+    /// The barcode at `index`, if there is one
+    pub fn get(&self, index: usize) -> Option<&BaseBits> {
+        self.barcodes.get(index)
+    }
+
+    /// The barcodes, in the order they were given
+    pub fn as_slice(&self) -> &[BaseBits] {
+        &self.barcodes
+    }
+
+    /// Find the barcode closest to `query` under n-any, returning its index and distance as
+    /// `nearest_nany` does. Returns `None` if the set is empty or `query` is not the set's length.
+    pub fn nearest(&self, query: &BaseBits) -> Option<(usize, u32)> {
+        if query.len != self.seq_len {
+            return None;
+        }
+        nearest_nany(query, &self.barcodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_barcode_set_nearest() {
+        let set = BarcodeSet::from_iter(["ACTGACTG", "GGTTCCAA", "TTAGGCAT", "CACANNTG"]).unwrap();
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.seq_len(), 8);
+
+        // One base off from entry 2
+        let query = BaseBits::new(b"TTAGCCAT").unwrap();
+        assert_eq!(set.nearest(&query), Some((2, 1)));
+        assert_eq!(set.nearest(set.get(0).unwrap()), Some((0, 0)));
+        assert_eq!(set.nearest(&BaseBits::new(b"TTAGCCA").unwrap()), None);
+    }
+
+    #[test]
+    fn test_barcode_set_validation() {
+        assert_eq!(
+            BarcodeSet::from_iter(vec![b"ACTG".to_vec(), b"ACT".to_vec()]),
+            Err(BaseBitsError::LengthMismatch {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert!(BarcodeSet::from_iter([&[b'A'; 30][..]]).is_err());
+
+        let empty = BarcodeSet::from_iter(Vec::<&str>::new()).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.nearest(&BaseBits::new(b"").unwrap()), None);
+    }
+}
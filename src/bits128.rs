@@ -0,0 +1,179 @@
+//! A `u128` backed version of `BaseBits` for sequences of up to 42 bases, using the same 3 bit
+//! encoding and N tracking.
+use crate::{base_char, dna_base, BaseBitsError, Bases, ENCODING_DIST, ENCODING_LENGTH};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{fmt, str};
+
+pub const CONTAINER_WIDTH_128: u32 = 128;
+pub const MAX_BASES_128: usize = (CONTAINER_WIDTH_128 / ENCODING_LENGTH) as usize;
+
+/// A BaseBits encoding backed by u128's
+#[derive(Hash, PartialEq, Eq, Debug, Copy, Clone)]
+pub struct BaseBits128 {
+    /// The u128 holding the encoding
+    pub code: u128,
+    /// The u128 holding an inverse encoding of N's
+    nbits: u128,
+    /// The length of the original input
+    len: usize,
+}
+
+impl BaseBits128 {
+    /// Create a new BaseBits128 object.
+    pub fn new(seq: &[u8]) -> Result<BaseBits128, BaseBitsError> {
+        let mut code: u128 = 0;
+        let mut nbits: u128 = !0b0;
+        let len = seq.len();
+        if len > MAX_BASES_128 {
+            return Err(BaseBitsError::TooLong {
+                len,
+                max: MAX_BASES_128,
+            });
+        }
+        for &c in seq.iter() {
+            let base = dna_base(c);
+
+            code = (code << ENCODING_LENGTH) | u128::from(base);
+            nbits = match base {
+                Bases::N => nbits << ENCODING_LENGTH,
+                _ => (nbits << ENCODING_LENGTH) | 0b111,
+            }
+        }
+        Ok(BaseBits128 { code, nbits, len })
+    }
+
+    /// Decode a BaseBits128 object into a string
+    pub fn decode(&self) -> Vec<u8> {
+        let mut s = Vec::new();
+        let mut code = self.code;
+        for _ in 0..self.len {
+            let base = (code & 0b111) as u64;
+            code >>= ENCODING_LENGTH;
+            s.push(base_char(base));
+        }
+        s.into_iter().rev().collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BaseBits128 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", str::from_utf8(&self.decode()).unwrap())
+    }
+}
+
+/// Compute hamming distance between two BaseBits128, count N's as any character
+#[inline]
+pub fn hamming_dist_nany_128(alpha: &BaseBits128, beta: &BaseBits128) -> u32 {
+    ((alpha.code ^ beta.code) & (alpha.nbits & beta.nbits)).count_ones() / ENCODING_DIST
+}
+
+/// Compute hamming distance between two BaseBits128, counting N's as mismatches. An N - N will
+/// still count as a mismatch
+#[inline]
+pub fn hamming_dist_none_128(alpha: &BaseBits128, beta: &BaseBits128) -> u32 {
+    let nbits_and = alpha.nbits & beta.nbits;
+    (((alpha.code ^ beta.code) & nbits_and).count_ones() / ENCODING_DIST)
+        + ((!nbits_and).count_ones() / ENCODING_LENGTH)
+}
+
+/// Check if `candidate` is within `k` mismatches of a cluster `representative`, counting N's as
+/// mismatches. The words are compared a u64 at a time, starting from the 3' end, and the
+/// comparison stops as soon as more than `k` mismatches have been found.
+pub fn is_member_128(candidate: &BaseBits128, representative: &BaseBits128, k: u32) -> bool {
+    // 63 bits holds a whole number of bases, and two chunks cover all MAX_BASES_128 of them
+    const CHUNK: u32 = 63;
+    const CHUNK_MASK: u64 = !0u64 >> 1;
+    let diff = candidate.code ^ representative.code;
+    let nbits_and = candidate.nbits & representative.nbits;
+    let mut dist = 0;
+    for &shift in &[0, CHUNK] {
+        let nbits = (nbits_and >> shift) as u64 & CHUNK_MASK;
+        dist += (((diff >> shift) as u64 & nbits).count_ones() / ENCODING_DIST)
+            + ((!nbits & CHUNK_MASK).count_ones() / ENCODING_LENGTH);
+        if dist > k {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bb128_30bp() {
+        let alpha = BaseBits128::new(b"ACTGACTGACTGACTGACTGACTGACTGAC").unwrap();
+        let beta = BaseBits128::new(b"ACTGACTGACTGACTGACTGACTGACTNAG").unwrap();
+        assert_eq!(hamming_dist_nany_128(&alpha, &beta), 1);
+        assert_eq!(hamming_dist_none_128(&alpha, &beta), 2);
+        assert_eq!(hamming_dist_nany_128(&alpha, &alpha), 0);
+    }
+
+    #[test]
+    fn test_is_member_128() {
+        let rep = BaseBits128::new(b"ACTGACTGACTGACTGACTGACTGACTGAC").unwrap();
+        let candidate = BaseBits128::new(b"TCTGACTGACTGACTGACTGACTGACTNAC").unwrap();
+        assert_eq!(hamming_dist_none_128(&candidate, &rep), 2);
+        assert!(is_member_128(&candidate, &rep, 2));
+        assert!(is_member_128(&candidate, &rep, 3));
+        assert!(!is_member_128(&candidate, &rep, 1));
+
+        let full = BaseBits128::new(&[b'N'; MAX_BASES_128]).unwrap();
+        assert_eq!(hamming_dist_none_128(&full, &full), 42);
+        assert!(is_member_128(&full, &full, 42));
+        assert!(!is_member_128(&full, &full, 41));
+    }
+
+    #[test]
+    fn test_bb128_matches_basebits() {
+        use crate::{hamming_dist_nany, hamming_dist_none, BaseBits};
+        let alpha_seq = b"ACTGNACTTGCA";
+        let beta_seq = b"ACNGTACTAGCN";
+        let alpha = BaseBits::new(alpha_seq).unwrap();
+        let beta = BaseBits::new(beta_seq).unwrap();
+        let alpha_128 = BaseBits128::new(alpha_seq).unwrap();
+        let beta_128 = BaseBits128::new(beta_seq).unwrap();
+        assert_eq!(alpha_128.code, u128::from(alpha.code));
+        assert_eq!(
+            hamming_dist_nany_128(&alpha_128, &beta_128),
+            hamming_dist_nany(&alpha, &beta)
+        );
+        assert_eq!(
+            hamming_dist_none_128(&alpha_128, &beta_128),
+            hamming_dist_none(&alpha, &beta)
+        );
+    }
+
+    #[test]
+    fn test_bb128_umi_lengths() {
+        use crate::BaseBits;
+        for len in 24..=32 {
+            let seq: Vec<u8> = b"ACTG".iter().cycle().take(len).cloned().collect();
+            let mut other = seq.clone();
+            other[len - 1] = b'N';
+            other[0] = b'T';
+            assert!(BaseBits::new(&seq).is_err());
+            let alpha = BaseBits128::new(&seq).unwrap();
+            let beta = BaseBits128::new(&other).unwrap();
+            assert_eq!(alpha.decode(), seq);
+            assert_eq!(hamming_dist_nany_128(&alpha, &beta), 1);
+            assert_eq!(hamming_dist_none_128(&alpha, &beta), 2);
+        }
+    }
+
+    #[test]
+    fn test_bb128_round_trip() {
+        let seq = b"GATACAGATACAACNATAGCAGATACAGATACAACNATAGCA";
+        assert_eq!(seq.len(), MAX_BASES_128);
+        let bb = BaseBits128::new(seq).unwrap();
+        assert_eq!(bb.decode(), seq.to_vec());
+        #[cfg(feature = "std")]
+        assert_eq!(bb.to_string(), str::from_utf8(seq).unwrap());
+
+        let too_long = [b'A'; MAX_BASES_128 + 1];
+        assert!(BaseBits128::new(&too_long).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Direct addressed exact membership for short barcodes.
+use crate::{BaseBits, BaseBitsError, Bases};
+use alloc::vec::Vec;
+
+/// The longest sequences an `ExactBitsetIndex` can hold. Each position can take one of 5 values
+/// (A, C, T, G, N), so 12 bases needs 5^12 bits, about 30MB.
+pub const MAX_BITSET_BASES: usize = 12;
+
+/// Exact membership for barcodes of one fixed length, backed by a bitset with one bit for every
+/// possible sequence. Lookups are a single bit test. N's are matched literally, not as wildcards.
+///
+/// A sequence's slot is the base 5 rank of its decoded bases, not its raw `code`. The 3 bit
+/// `code` would need 8^len slots, most of them unused, where the rank packs every sequence into
+/// 5^len.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ExactBitsetIndex {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl ExactBitsetIndex {
+    /// Create an empty index for sequences of length `len`, erroring if `len` exceeds
+    /// `MAX_BITSET_BASES`.
+    pub fn new(len: usize) -> Result<ExactBitsetIndex, BaseBitsError> {
+        if len > MAX_BITSET_BASES {
+            return Err(BaseBitsError::TooLong {
+                len,
+                max: MAX_BITSET_BASES,
+            });
+        }
+        let space = 5usize.pow(len as u32);
+        Ok(ExactBitsetIndex {
+            bits: vec![0; space.div_ceil(64)],
+            len,
+        })
+    }
+
+    /// The sequence length this index holds
+    pub fn seq_len(&self) -> usize {
+        self.len
+    }
+
+    /// Add `bb` to the index, returning true if it was not already present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bb` is not the length the index was created for.
+    pub fn insert(&mut self, bb: &BaseBits) -> bool {
+        assert_eq!(bb.len, self.len, "sequence length doesn't match the index");
+        let slot = slot(bb);
+        let word = &mut self.bits[slot / 64];
+        let bit = 1 << (slot % 64);
+        let added = *word & bit == 0;
+        *word |= bit;
+        added
+    }
+
+    /// Check if `bb` is in the index. Sequences of a different length are never present.
+    pub fn contains(&self, bb: &BaseBits) -> bool {
+        if bb.len != self.len {
+            return false;
+        }
+        let slot = slot(bb);
+        self.bits[slot / 64] & (1 << (slot % 64)) != 0
+    }
+}
+
+/// Dense rank of a sequence, reading each base as a base 5 digit
+fn slot(bb: &BaseBits) -> usize {
+    (0..bb.len).fold(0, |acc, pos| {
+        acc * 5
+            + match bb.base_at(pos) {
+                Bases::A => 0,
+                Bases::C => 1,
+                Bases::T => 2,
+                Bases::G => 3,
+                _ => 4,
+            }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_bitset_index() {
+        let mut index = ExactBitsetIndex::new(8).unwrap();
+        let members = [&b"ACTGACTG"[..], b"GGGGGGGG", b"NNNNNNNN", b"TTTTTTTT"];
+        for seq in &members {
+            assert!(index.insert(&BaseBits::new(seq).unwrap()));
+        }
+        assert!(!index.insert(&BaseBits::new(b"GGGGGGGG").unwrap()));
+        for seq in &members {
+            assert!(index.contains(&BaseBits::new(seq).unwrap()));
+        }
+        assert!(!index.contains(&BaseBits::new(b"ACTGACTA").unwrap()));
+        assert!(!index.contains(&BaseBits::new(b"NCTGACTG").unwrap()));
+        assert!(!index.contains(&BaseBits::new(b"AAAAAAAA").unwrap()));
+        assert!(!index.contains(&BaseBits::new(b"ACTGACT").unwrap()));
+    }
+
+    #[test]
+    fn test_exact_bitset_index_too_long() {
+        assert_eq!(
+            ExactBitsetIndex::new(MAX_BITSET_BASES + 1),
+            Err(BaseBitsError::TooLong {
+                len: MAX_BITSET_BASES + 1,
+                max: MAX_BITSET_BASES
+            })
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Incremental construction of a `BaseBits` one base at a time.
+use crate::{dna_base, BaseBits, BaseBitsError, Bases, ENCODING_LENGTH, MAX_BASES};
+
+/// Builds a BaseBits by pushing bases onto the 3' end. Bases are shifted straight into the
+/// encoding so there are no intermediate buffers to allocate.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct BaseBitsBuilder {
+    bits: BaseBits,
+    capacity: usize,
+}
+
+impl Default for BaseBitsBuilder {
+    fn default() -> BaseBitsBuilder {
+        BaseBitsBuilder::new()
+    }
+}
+
+impl BaseBitsBuilder {
+    /// Create an empty builder that can hold up to `MAX_BASES` bases
+    pub fn new() -> BaseBitsBuilder {
+        BaseBitsBuilder {
+            bits: BaseBits {
+                code: 0,
+                nbits: !0,
+                len: 0,
+            },
+            capacity: MAX_BASES,
+        }
+    }
+
+    /// Create an empty builder that will hold at most `capacity` bases, erroring up front if
+    /// `capacity` exceeds `MAX_BASES` rather than part way through building.
+    pub fn with_capacity(capacity: usize) -> Result<BaseBitsBuilder, BaseBitsError> {
+        if capacity > MAX_BASES {
+            return Err(BaseBitsError::TooLong {
+                len: capacity,
+                max: MAX_BASES,
+            });
+        }
+        Ok(BaseBitsBuilder {
+            capacity,
+            ..BaseBitsBuilder::new()
+        })
+    }
+
+    /// The most bases this builder will hold
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of bases pushed so far
+    pub fn len(&self) -> usize {
+        self.bits.len
+    }
+
+    /// Check if no bases have been pushed
+    pub fn is_empty(&self) -> bool {
+        self.bits.len == 0
+    }
+
+    /// Push a base onto the 3' end, with the same character handling as `BaseBits::new`.
+    /// Errors if the builder is already at capacity.
+    pub fn push(&mut self, c: u8) -> Result<(), BaseBitsError> {
+        if self.bits.len == self.capacity {
+            return Err(BaseBitsError::TooLong {
+                len: self.capacity + 1,
+                max: self.capacity,
+            });
+        }
+        let base = dna_base(c);
+        self.bits.code = (self.bits.code << ENCODING_LENGTH) | base;
+        self.bits.nbits = match base {
+            Bases::N => self.bits.nbits << ENCODING_LENGTH,
+            _ => (self.bits.nbits << ENCODING_LENGTH) | 0b111,
+        };
+        self.bits.len += 1;
+        Ok(())
+    }
+
+    /// Finish building
+    pub fn build(&self) -> BaseBits {
+        self.bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let mut builder = BaseBitsBuilder::new();
+        for &c in b"ACTGN" {
+            builder.push(c).unwrap();
+        }
+        assert_eq!(builder.len(), 5);
+        assert_eq!(builder.build(), BaseBits::new(b"ACTGN").unwrap());
+        assert_eq!(BaseBitsBuilder::new().build(), BaseBits::new(b"").unwrap());
+    }
+
+    #[test]
+    fn test_builder_with_capacity() {
+        let mut builder = BaseBitsBuilder::with_capacity(4).unwrap();
+        for &c in b"ACTG" {
+            builder.push(c).unwrap();
+        }
+        assert_eq!(builder.build(), BaseBits::new(b"ACTG").unwrap());
+        assert_eq!(
+            builder.push(b'A'),
+            Err(BaseBitsError::TooLong { len: 5, max: 4 })
+        );
+        assert_eq!(builder.build(), BaseBits::new(b"ACTG").unwrap());
+
+        assert!(BaseBitsBuilder::with_capacity(MAX_BASES).is_ok());
+        assert_eq!(
+            BaseBitsBuilder::with_capacity(MAX_BASES + 1),
+            Err(BaseBitsError::TooLong {
+                len: MAX_BASES + 1,
+                max: MAX_BASES
+            })
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Column (struct of arrays) storage for many `BaseBits` of the same length. Keeping the `code`
+//! and `nbits` words in their own contiguous vectors makes passes over a single field cache
+//! friendly.
+use crate::{dist_nany_raw, extract_bits, BaseBits, BaseBitsError, ENCODING_LENGTH};
+use alloc::vec::Vec;
+
+/// A column of same length BaseBits encodings
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct BaseBitsColumn {
+    codes: Vec<u64>,
+    nbits: Vec<u64>,
+    /// The shared length of every entry
+    seq_len: usize,
+}
+
+impl BaseBitsColumn {
+    /// Create a new column from `seqs`, erroring if they are not all the same length.
+    pub fn new(seqs: &[BaseBits]) -> Result<BaseBitsColumn, BaseBitsError> {
+        let seq_len = seqs.first().map_or(0, |bb| bb.len);
+        if let Some(bb) = seqs.iter().find(|bb| bb.len != seq_len) {
+            return Err(BaseBitsError::LengthMismatch {
+                expected: seq_len,
+                found: bb.len,
+            });
+        }
+        Ok(BaseBitsColumn {
+            codes: seqs.iter().map(|bb| bb.code).collect(),
+            nbits: seqs.iter().map(|bb| bb.nbits).collect(),
+            seq_len,
+        })
+    }
+
+    /// The number of entries in the column
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Check if the column has no entries
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// The length of the sequences stored in the column
+    pub fn seq_len(&self) -> usize {
+        self.seq_len
+    }
+
+    /// Get the entry at index `i`
+    pub fn get(&self, i: usize) -> Option<BaseBits> {
+        Some(BaseBits {
+            code: *self.codes.get(i)?,
+            nbits: self.nbits[i],
+            len: self.seq_len,
+        })
+    }
+
+    /// For each position (5' -> 3'), the fraction of entries that have an N there. An empty
+    /// column returns an empty vector.
+    pub fn n_rate_per_position(&self) -> Vec<f64> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        (0..self.seq_len)
+            .map(|pos| {
+                let shift = (self.seq_len - 1 - pos) as u32 * ENCODING_LENGTH;
+                let ns = self
+                    .nbits
+                    .iter()
+                    .filter(|&&nbits| extract_bits(nbits >> shift, ENCODING_LENGTH) == 0)
+                    .count();
+                ns as f64 / self.len() as f64
+            })
+            .collect()
+    }
+}
+
+/// Histogram of each query's distance to its nearest reference under n-any, where entry `d` is the
+/// number of queries whose closest reference is `d` away. The histogram has one bin for every
+/// possible distance, `0..=max(queries.seq_len(), refs.seq_len())`, since columns of differing
+/// lengths can be further apart than the shorter length. If there are no references every bin is 0.
+pub fn cross_histogram_nany(queries: &BaseBitsColumn, refs: &BaseBitsColumn) -> Vec<u64> {
+    let mut hist = vec![0; queries.seq_len.max(refs.seq_len) + 1];
+    if refs.is_empty() {
+        return hist;
+    }
+    for (&q_code, &q_nbits) in queries.codes.iter().zip(queries.nbits.iter()) {
+        let nearest = refs
+            .codes
+            .iter()
+            .zip(refs.nbits.iter())
+            .map(|(&r_code, &r_nbits)| dist_nany_raw((q_code, q_nbits), (r_code, r_nbits)))
+            .min()
+            .unwrap_or(0);
+        hist[nearest as usize] += 1;
+    }
+    hist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(seqs: &[&[u8]]) -> BaseBitsColumn {
+        let seqs: Vec<BaseBits> = seqs.iter().map(|s| BaseBits::new(s).unwrap()).collect();
+        BaseBitsColumn::new(&seqs).unwrap()
+    }
+
+    #[test]
+    fn test_column_new() {
+        let col = column(&[b"ACTG", b"ACNG"]);
+        assert_eq!(col.len(), 2);
+        assert_eq!(col.seq_len(), 4);
+        assert_eq!(col.get(1), Some(BaseBits::new(b"ACNG").unwrap()));
+        assert_eq!(col.get(2), None);
+
+        let mixed = [
+            BaseBits::new(b"ACTG").unwrap(),
+            BaseBits::new(b"ACT").unwrap(),
+        ];
+        assert_eq!(
+            BaseBitsColumn::new(&mixed),
+            Err(BaseBitsError::LengthMismatch {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert!(BaseBitsColumn::new(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cross_histogram_nany() {
+        let refs = column(&[b"AAAA", b"CCCC", b"GGGG"]);
+        let queries = column(&[b"AAAA", b"CCCA", b"GGTT", b"NNNN", b"TTTT", b"ACCC"]);
+        assert_eq!(cross_histogram_nany(&queries, &refs), vec![2, 2, 1, 0, 1]);
+        assert_eq!(cross_histogram_nany(&queries, &column(&[])), vec![0; 5]);
+
+        // Longer references can be further away than the query length
+        let short = column(&[b"AA"]);
+        let long = column(&[b"CCCCCC"]);
+        let hist = cross_histogram_nany(&short, &long);
+        assert_eq!(hist.len(), 7);
+        assert_eq!(hist[6], 1);
+        assert_eq!(cross_histogram_nany(&long, &short).len(), 7);
+    }
+
+    #[test]
+    fn test_n_rate_per_position() {
+        let col = column(&[b"ACNG", b"ACTG", b"NCNG", b"ACTG"]);
+        assert_eq!(col.n_rate_per_position(), vec![0.25, 0.0, 0.5, 0.0]);
+        assert!(column(&[]).n_rate_per_position().is_empty());
+    }
+}
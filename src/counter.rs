@@ -0,0 +1,209 @@
+//! Counting of exact barcode occurrences.
+use crate::{hamming_dist_nany, BaseBits};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+/// Counts how many times each exact encoding has been seen
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct SeqCounter {
+    counts: HashMap<BaseBits, u64>,
+}
+
+#[cfg(feature = "std")]
+impl SeqCounter {
+    /// Create an empty counter
+    pub fn new() -> SeqCounter {
+        SeqCounter::default()
+    }
+
+    /// Create an empty counter with room for at least `capacity` distinct sequences
+    pub fn with_capacity(capacity: usize) -> SeqCounter {
+        SeqCounter {
+            counts: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Record an occurrence of `bb`
+    pub fn add(&mut self, bb: BaseBits) {
+        *self.counts.entry(bb).or_insert(0) += 1;
+    }
+
+    /// The number of times `bb` has been seen
+    pub fn count(&self, bb: &BaseBits) -> u64 {
+        self.counts.get(bb).copied().unwrap_or(0)
+    }
+
+    /// Stop tracking `bb`, returning its count if it had been seen
+    pub fn remove(&mut self, bb: &BaseBits) -> Option<u64> {
+        self.counts.remove(bb)
+    }
+
+    /// Remove all counts, keeping the allocated capacity
+    pub fn clear(&mut self) {
+        self.counts.clear()
+    }
+
+    /// The number of distinct sequences seen
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Check if no sequences have been seen
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The number of distinct sequences the counter can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.counts.capacity()
+    }
+
+    /// Release excess capacity. Removing or clearing entries never gives memory back, so a long
+    /// running service that ingests bursts of barcodes should call this after a burst has been
+    /// drained to drop back down to what the remaining entries need.
+    pub fn shrink_to_fit(&mut self) {
+        self.counts.shrink_to_fit()
+    }
+
+    /// Iterate over each distinct sequence and its count, in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = (&BaseBits, &u64)> {
+        self.counts.iter()
+    }
+}
+
+/// Counts sequences, bucketing together sequences that are equal when N's are treated as
+/// wildcards, so `NCTG` and `ACTG` share a count.
+///
+/// Wildcard equality isn't transitive (`ANG` matches both `ACG` and `ATG`, which don't match each
+/// other), so buckets are resolved first seen first: each bucket keeps a key, and a sequence
+/// joins the oldest bucket whose key it matches, filling any N's in the key with its own called
+/// bases. A key only gets more specific over time, so once `ANG` has been joined by `ACG` its key
+/// is `ACG` and a later `ATG` starts a bucket of its own. Lookups scan the buckets linearly.
+#[derive(Debug, Clone, Default)]
+pub struct WildcardCounter {
+    buckets: Vec<(BaseBits, u64)>,
+}
+
+impl WildcardCounter {
+    /// Create an empty counter
+    pub fn new() -> WildcardCounter {
+        WildcardCounter::default()
+    }
+
+    /// Record an occurrence of `bb`
+    pub fn add(&mut self, bb: BaseBits) {
+        match self.find(&bb) {
+            Some(i) => {
+                let (key, count) = &mut self.buckets[i];
+                *key = key.resolve_against(&bb);
+                *count += 1;
+            }
+            None => self.buckets.push((bb, 1)),
+        }
+    }
+
+    /// The count of the bucket `bb` falls into, or 0 if it matches none
+    pub fn count(&self, bb: &BaseBits) -> u64 {
+        self.find(bb).map_or(0, |i| self.buckets[i].1)
+    }
+
+    /// The number of buckets
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Check if nothing has been counted
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Iterate over each bucket's resolved key and count, oldest bucket first
+    pub fn iter(&self) -> impl Iterator<Item = (&BaseBits, &u64)> {
+        self.buckets.iter().map(|(key, count)| (key, count))
+    }
+
+    /// Index of the oldest bucket whose key matches `bb` under n-any
+    fn find(&self, bb: &BaseBits) -> Option<usize> {
+        self.buckets
+            .iter()
+            .position(|(key, _)| key.len == bb.len && hamming_dist_nany(key, bb) == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_seq_counter() {
+        let mut counter = SeqCounter::new();
+        let alpha = BaseBits::new(b"ACTG").unwrap();
+        let beta = BaseBits::new(b"ACTN").unwrap();
+        counter.add(alpha);
+        counter.add(alpha);
+        counter.add(beta);
+        assert_eq!(counter.count(&alpha), 2);
+        assert_eq!(counter.count(&beta), 1);
+        assert_eq!(counter.count(&BaseBits::new(b"AAAA").unwrap()), 0);
+        assert_eq!(counter.len(), 2);
+        assert_eq!(counter.remove(&alpha), Some(2));
+        assert_eq!(counter.count(&alpha), 0);
+        counter.clear();
+        assert!(counter.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut counter = SeqCounter::new();
+        let seqs: Vec<BaseBits> = (0..1000u64)
+            .map(|i| {
+                let seq: Vec<u8> = (0..6)
+                    .map(|j| b"ACTG"[((i >> (2 * j)) % 4) as usize])
+                    .collect();
+                BaseBits::new(&seq).unwrap()
+            })
+            .collect();
+        for bb in &seqs {
+            counter.add(*bb);
+        }
+        for bb in &seqs[4..] {
+            counter.remove(bb);
+        }
+        let before = counter.capacity();
+        counter.shrink_to_fit();
+        assert!(counter.capacity() < before);
+        assert_eq!(counter.len(), 4);
+        assert_eq!(counter.count(&seqs[0]), 1);
+
+        let mut counter = SeqCounter::with_capacity(1000);
+        counter.add(seqs[0]);
+        counter.shrink_to_fit();
+        assert!(counter.capacity() < 1000);
+    }
+
+    #[test]
+    fn test_wildcard_counter() {
+        let mut counter = WildcardCounter::new();
+        counter.add(BaseBits::new(b"NCTG").unwrap());
+        counter.add(BaseBits::new(b"ACTG").unwrap());
+        counter.add(BaseBits::new(b"ACTN").unwrap());
+        assert_eq!(counter.count(&BaseBits::new(b"ACTG").unwrap()), 3);
+        assert_eq!(counter.count(&BaseBits::new(b"NCTG").unwrap()), 3);
+        assert_eq!(counter.len(), 1);
+        assert_eq!(
+            counter.iter().next(),
+            Some((&BaseBits::new(b"ACTG").unwrap(), &3))
+        );
+
+        // The key resolved to ACTG, so a sequence conflicting at the filled position is separate
+        counter.add(BaseBits::new(b"TCTG").unwrap());
+        assert_eq!(counter.count(&BaseBits::new(b"TCTG").unwrap()), 1);
+        assert_eq!(counter.len(), 2);
+        assert_eq!(counter.count(&BaseBits::new(b"GGGG").unwrap()), 0);
+        assert_eq!(counter.count(&BaseBits::new(b"ACT").unwrap()), 0);
+    }
+}
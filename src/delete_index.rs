@@ -0,0 +1,117 @@
+//! A deletion variant index for finding stored barcodes within one substitution of a query.
+use crate::BaseBits;
+use std::collections::HashMap;
+
+/// The low bit of every 3 bit group
+const GROUP_LOW_BITS: u64 = 0x1249_2492_4924_9249;
+
+/// Finds stored barcodes within one substitution of a query without scanning them all. Every
+/// stored barcode is filed under each of its `BaseBits::fuzzy_keys`, and a query gathers the
+/// barcodes filed under its own keys as candidates, which are then verified. Two same length
+/// sequences with at most one differing position always share a key, so no match is missed.
+///
+/// Bases are compared literally here, an N only matches an N, since the deletion keys can't
+/// treat N's as wildcards.
+#[derive(Debug, Clone, Default)]
+pub struct Delete1Index {
+    seqs: Vec<BaseBits>,
+    buckets: HashMap<u64, Vec<usize>>,
+}
+
+impl Delete1Index {
+    /// Create an empty index
+    pub fn new() -> Delete1Index {
+        Delete1Index::default()
+    }
+
+    /// The number of barcodes stored
+    pub fn len(&self) -> usize {
+        self.seqs.len()
+    }
+
+    /// Check if no barcodes are stored
+    pub fn is_empty(&self) -> bool {
+        self.seqs.is_empty()
+    }
+
+    /// The barcode stored at `index`, if there is one
+    pub fn get(&self, index: usize) -> Option<&BaseBits> {
+        self.seqs.get(index)
+    }
+
+    /// Store `bb`, returning its index. Duplicates are stored again under a new index.
+    pub fn insert(&mut self, bb: BaseBits) -> usize {
+        let index = self.seqs.len();
+        for key in bb.fuzzy_keys() {
+            self.buckets.entry(key).or_default().push(index);
+        }
+        self.seqs.push(bb);
+        index
+    }
+
+    /// The indices of every stored barcode of the same length as `query` that differs from it at
+    /// no more than one position, in ascending order.
+    pub fn query_within_1(&self, query: &BaseBits) -> Vec<usize> {
+        let mut found: Vec<usize> = query
+            .fuzzy_keys()
+            .iter()
+            .filter_map(|key| self.buckets.get(key))
+            .flatten()
+            .cloned()
+            .filter(|&i| {
+                let stored = &self.seqs[i];
+                stored.len() == query.len() && literal_dist(stored, query) <= 1
+            })
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+/// The number of positions where the two encodings hold different codes, so an N only matches
+/// an N
+fn literal_dist(alpha: &BaseBits, beta: &BaseBits) -> u32 {
+    let diff = alpha.code ^ beta.code;
+    ((diff | (diff >> 1) | (diff >> 2)) & GROUP_LOW_BITS).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete1_index() {
+        let stored = [
+            &b"ACTGACTG"[..],
+            b"ACTGACTT",
+            b"TCTGACTG",
+            b"ACTGGGTG",
+            b"GGGGCCCC",
+            b"ACTGACT",
+            b"ACTNACTG",
+            b"ACTGACTG",
+        ];
+        let mut index = Delete1Index::new();
+        for seq in &stored {
+            index.insert(BaseBits::new(seq).unwrap());
+        }
+        assert_eq!(index.len(), stored.len());
+
+        let query = BaseBits::new(b"ACTGACTG").unwrap();
+        let naive: Vec<usize> = (0..index.len())
+            .filter(|&i| {
+                let bb = index.get(i).unwrap();
+                bb.len() == query.len() && literal_dist(bb, &query) <= 1
+            })
+            .collect();
+        assert_eq!(naive, vec![0, 1, 2, 6, 7]);
+        assert_eq!(index.query_within_1(&query), naive);
+
+        let query = BaseBits::new(b"GGGGCCCA").unwrap();
+        assert_eq!(index.query_within_1(&query), vec![4]);
+        let query = BaseBits::new(b"TTTTTTTT").unwrap();
+        assert!(index.query_within_1(&query).is_empty());
+        assert!(Delete1Index::new().query_within_1(&query).is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+//! Edit distances, for sequences that may differ by insertions and deletions as well as
+//! substitutions. These work on plain bytes rather than encodings, since the XOR trick only
+//! applies to aligned, same length sequences.
+
+pub mod levenshtein {
+    use alloc::vec::Vec;
+
+    /// The Levenshtein distance between two sequences, the fewest single base substitutions,
+    /// insertions, and deletions needed to turn `alpha` into `beta`. Bytes are compared literally,
+    /// so an N only matches another N.
+    pub fn distance(alpha: &[u8], beta: &[u8]) -> usize {
+        let mut prev: Vec<usize> = (0..=beta.len()).collect();
+        let mut curr = vec![0; beta.len() + 1];
+        for (i, &a) in alpha.iter().enumerate() {
+            curr[0] = i + 1;
+            for (j, &b) in beta.iter().enumerate() {
+                let substitution = prev[j] + usize::from(a != b);
+                curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+            }
+            core::mem::swap(&mut prev, &mut curr);
+        }
+        prev[beta.len()]
+    }
+
+    /// The Levenshtein distance between two sequences if it is at most `max`, otherwise `None`.
+    /// Only the diagonal band of width `2 * max + 1` can hold a distance within `max`, so just
+    /// that band is filled in, and the scan stops as soon as a whole row of the band is over
+    /// `max`.
+    pub fn distance_within(alpha: &[u8], beta: &[u8], max: usize) -> Option<usize> {
+        let (len_a, len_b) = (alpha.len(), beta.len());
+        if len_a.max(len_b) - len_a.min(len_b) > max {
+            return None;
+        }
+        // Anything over max is as good as infinite
+        let over = max + 1;
+        let mut prev: Vec<usize> = (0..=len_b).map(|j| j.min(over)).collect();
+        let mut curr = vec![over; len_b + 1];
+        for (i, &a) in alpha.iter().enumerate() {
+            let row = i + 1;
+            let lo = row.saturating_sub(max);
+            let hi = (row + max).min(len_b);
+            curr[0] = row.min(over);
+            if lo > 1 {
+                curr[lo - 1] = over;
+            }
+            let mut row_min = if lo == 0 { curr[0] } else { over };
+            for j in lo.max(1)..=hi {
+                let substitution = prev[j - 1] + usize::from(a != beta[j - 1]);
+                let cell = substitution.min(prev[j] + 1).min(curr[j - 1] + 1).min(over);
+                curr[j] = cell;
+                row_min = row_min.min(cell);
+            }
+            if hi < len_b {
+                curr[hi + 1] = over;
+            }
+            if row_min > max {
+                return None;
+            }
+            core::mem::swap(&mut prev, &mut curr);
+        }
+        Some(prev[len_b]).filter(|&dist| dist <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::levenshtein::*;
+
+    #[test]
+    fn test_levenshtein_distance() {
+        assert_eq!(distance(b"ACTG", b"ACG"), 1);
+        assert_eq!(distance(b"ACTG", b"ACTGA"), 1);
+        assert_eq!(distance(b"ACTG", b"ACTG"), 0);
+        assert_eq!(distance(b"ACTG", b"AGTG"), 1);
+        assert_eq!(distance(b"", b"ACT"), 3);
+        assert_eq!(distance(b"ACTGACTG", b"CTGACTGA"), 2);
+        assert_eq!(distance(b"GATTACA", b"TACAGAT"), 5);
+    }
+
+    #[test]
+    fn test_levenshtein_distance_within() {
+        let seqs: [&[u8]; 8] = [
+            b"ACTG",
+            b"ACG",
+            b"ACTGA",
+            b"",
+            b"CTGACTGA",
+            b"ACTGACTG",
+            b"GATTACA",
+            b"TACAGAT",
+        ];
+        for alpha in &seqs {
+            for beta in &seqs {
+                let dist = distance(alpha, beta);
+                for max in 0..10 {
+                    let expected = Some(dist).filter(|&d| d <= max);
+                    assert_eq!(distance_within(alpha, beta, max), expected);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! The error type shared across the crate.
+use core::fmt;
+
+/// Errors that can occur while encoding or combining sequences
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[non_exhaustive]
+pub enum BaseBitsError {
+    /// The sequence has more bases than the container can hold
+    TooLong { len: usize, max: usize },
+    /// Sequences that must all be the same length are not
+    LengthMismatch { expected: usize, found: usize },
+    /// The sequence holds a character the encoding can't represent
+    InvalidBase { pos: usize, base: u8 },
+}
+
+impl fmt::Display for BaseBitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BaseBitsError::TooLong { len, max } => write!(
+                f,
+                "Length of string to encode ({}) exceeds the maximum of {} bases",
+                len, max
+            ),
+            BaseBitsError::LengthMismatch { expected, found } => write!(
+                f,
+                "Sequence length mismatch: expected {} bases, found {}",
+                expected, found
+            ),
+            BaseBitsError::InvalidBase { pos, base } => write!(
+                f,
+                "Unsupported base {:?} at position {}",
+                char::from(*base),
+                pos
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BaseBitsError {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{BaseBits, MAX_BASES};
+    use std::error::Error;
+
+    fn encode_boxed(seq: &[u8]) -> Result<BaseBits, Box<dyn Error>> {
+        Ok(BaseBits::new(seq)?)
+    }
+
+    #[test]
+    fn test_too_long() {
+        let err = BaseBits::new(&[b'A'; MAX_BASES + 3]).unwrap_err();
+        assert_eq!(
+            err,
+            BaseBitsError::TooLong {
+                len: MAX_BASES + 3,
+                max: MAX_BASES
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Length of string to encode (24) exceeds the maximum of 21 bases"
+        );
+        assert!(encode_boxed(&[b'A'; MAX_BASES + 1]).is_err());
+        assert!(encode_boxed(b"ACTG").is_ok());
+    }
+}
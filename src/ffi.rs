@@ -0,0 +1,153 @@
+//! C ABI wrappers around the encoding and distance kernels so non-Rust tools can use them.
+//! Enabled with the `ffi` feature. Sequences are passed around as their raw `code` and `nbits`
+//! integers. The matching C declarations are in `include/basebits.h`, and the README shows how
+//! to build the library as a cdylib or staticlib to link against.
+use crate::{hamming_dist_nany, hamming_dist_none, BaseBits, MAX_BASES};
+use core::slice;
+
+/// The longest sequence that can be encoded, `MAX_BASES`
+pub const BASEBITS_MAX_BASES: usize = MAX_BASES;
+
+/// Encoding succeeded
+pub const BASEBITS_OK: i32 = 0;
+/// A required pointer was null
+pub const BASEBITS_ERR_NULL: i32 = -1;
+/// The sequence is longer than `BASEBITS_MAX_BASES`
+pub const BASEBITS_ERR_TOO_LONG: i32 = -2;
+
+/// Encode `len` bytes starting at `ptr`, writing the `code` and `nbits` of the encoding to
+/// `out_code` and `out_nbits`. Returns `BASEBITS_OK` on success or one of the `BASEBITS_ERR_*`
+/// codes, in which case the outputs are left untouched.
+///
+/// # Safety
+///
+/// `ptr` must point to `len` readable bytes and `out_code` and `out_nbits` must be valid for
+/// writes. `ptr` may be null only if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn basebits_encode(
+    ptr: *const u8,
+    len: usize,
+    out_code: *mut u64,
+    out_nbits: *mut u64,
+) -> i32 {
+    if (ptr.is_null() && len != 0) || out_code.is_null() || out_nbits.is_null() {
+        return BASEBITS_ERR_NULL;
+    }
+    let seq = if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len)
+    };
+    match BaseBits::new(seq) {
+        Ok(bb) => {
+            *out_code = bb.code;
+            *out_nbits = bb.nbits;
+            BASEBITS_OK
+        }
+        Err(_) => BASEBITS_ERR_TOO_LONG,
+    }
+}
+
+/// Compute the n-any distance between two encodings from their raw parts. Both must have been
+/// encoded from sequences of the same length.
+#[no_mangle]
+pub extern "C" fn basebits_dist_nany(
+    alpha_code: u64,
+    alpha_nbits: u64,
+    beta_code: u64,
+    beta_nbits: u64,
+) -> u32 {
+    hamming_dist_nany(
+        &from_raw(alpha_code, alpha_nbits),
+        &from_raw(beta_code, beta_nbits),
+    )
+}
+
+/// Compute the n-none distance between two encodings from their raw parts. Both must have been
+/// encoded from sequences of the same length.
+#[no_mangle]
+pub extern "C" fn basebits_dist_none(
+    alpha_code: u64,
+    alpha_nbits: u64,
+    beta_code: u64,
+    beta_nbits: u64,
+) -> u32 {
+    hamming_dist_none(
+        &from_raw(alpha_code, alpha_nbits),
+        &from_raw(beta_code, beta_nbits),
+    )
+}
+
+/// The distance functions only look at `code` and `nbits`, so the length can be left at 0
+#[inline]
+fn from_raw(code: u64, nbits: u64) -> BaseBits {
+    BaseBits {
+        code,
+        nbits,
+        len: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(seq: &[u8]) -> Result<(u64, u64), i32> {
+        let mut code = 0;
+        let mut nbits = 0;
+        let status = unsafe { basebits_encode(seq.as_ptr(), seq.len(), &mut code, &mut nbits) };
+        match status {
+            BASEBITS_OK => Ok((code, nbits)),
+            err => Err(err),
+        }
+    }
+
+    #[test]
+    fn test_ffi_encode() {
+        let bb = BaseBits::new(b"ACTGN").unwrap();
+        assert_eq!(encode(b"ACTGN"), Ok((bb.code, bb.nbits)));
+        assert_eq!(
+            encode(b"ACTGACTGACTGACTGACTGAC"),
+            Err(BASEBITS_ERR_TOO_LONG)
+        );
+
+        let mut code = 0;
+        let status =
+            unsafe { basebits_encode(b"ACTG".as_ptr(), 4, &mut code, std::ptr::null_mut()) };
+        assert_eq!(status, BASEBITS_ERR_NULL);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_header_matches_constants() {
+        let header = include_str!("../include/basebits.h");
+        for (name, value) in [
+            ("BASEBITS_MAX_BASES", BASEBITS_MAX_BASES.to_string()),
+            ("BASEBITS_OK", BASEBITS_OK.to_string()),
+            ("BASEBITS_ERR_NULL", format!("({})", BASEBITS_ERR_NULL)),
+            (
+                "BASEBITS_ERR_TOO_LONG",
+                format!("({})", BASEBITS_ERR_TOO_LONG),
+            ),
+        ] {
+            let define = format!("#define {} {}\n", name, value);
+            assert!(header.contains(&define), "header is missing `{}`", define);
+        }
+    }
+
+    #[test]
+    fn test_ffi_dist() {
+        let (a_code, a_nbits) = encode(b"ACTGN").unwrap();
+        let (b_code, b_nbits) = encode(b"ACTTA").unwrap();
+        let alpha = BaseBits::new(b"ACTGN").unwrap();
+        let beta = BaseBits::new(b"ACTTA").unwrap();
+        assert_eq!(
+            basebits_dist_nany(a_code, a_nbits, b_code, b_nbits),
+            hamming_dist_nany(&alpha, &beta)
+        );
+        assert_eq!(
+            basebits_dist_none(a_code, a_nbits, b_code, b_nbits),
+            hamming_dist_none(&alpha, &beta)
+        );
+    }
+}
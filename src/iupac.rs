@@ -0,0 +1,138 @@
+//! An encoding of IUPAC ambiguity codes, where each position holds the set of bases it allows.
+//! Each base is 4 bits, one per nucleotide, so a u64 holds up to 16 bases.
+use crate::BaseBitsError;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{fmt, str};
+
+pub const IUPAC_ENCODING_LENGTH: u32 = 4;
+pub const MAX_IUPAC_BASES: usize = (64 / IUPAC_ENCODING_LENGTH) as usize;
+
+const A: u64 = 0b0001;
+const C: u64 = 0b0010;
+const G: u64 = 0b0100;
+const T: u64 = 0b1000;
+
+/// The IUPAC symbols for each of the 16 base sets, indexed by the set's bits
+const SYMBOLS: &[u8; 16] = b"-ACMGRSVTWYHKDBN";
+
+/// The low bit of every base
+const LOW_BITS: u64 = 0x1111_1111_1111_1111;
+
+/// A sequence of IUPAC codes, each position encoded as the set of bases it allows
+#[derive(Hash, PartialEq, Eq, Debug, Copy, Clone)]
+pub struct IupacBits {
+    /// The u64 holding a 4 bit base set per position
+    pub code: u64,
+    /// The length of the original input
+    len: usize,
+}
+
+impl IupacBits {
+    /// Create a new IupacBits object. Both two and three base ambiguity codes are supported, and
+    /// `U` is read as `T`. Anything unrecognized, including `N`, allows all four bases.
+    ///
+    /// Unlike `BaseBits::new`, which treats lowercase bases as N's, this is case-insensitive, so
+    /// soft-masked `acgt` encodes the same as `ACGT` rather than as a run of wildcards.
+    pub fn new(seq: &[u8]) -> Result<IupacBits, BaseBitsError> {
+        let len = seq.len();
+        if len > MAX_IUPAC_BASES {
+            return Err(BaseBitsError::TooLong {
+                len,
+                max: MAX_IUPAC_BASES,
+            });
+        }
+        let mut code = 0;
+        for c in seq {
+            let set = match c.to_ascii_uppercase() {
+                b'A' => A,
+                b'C' => C,
+                b'G' => G,
+                b'T' | b'U' => T,
+                b'R' => A | G,
+                b'Y' => C | T,
+                b'S' => C | G,
+                b'W' => A | T,
+                b'K' => G | T,
+                b'M' => A | C,
+                b'B' => C | G | T,
+                b'D' => A | G | T,
+                b'H' => A | C | T,
+                b'V' => A | C | G,
+                _ => A | C | G | T,
+            };
+            code = (code << IUPAC_ENCODING_LENGTH) | set;
+        }
+        Ok(IupacBits { code, len })
+    }
+
+    /// The number of bases encoded
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no bases are encoded
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decode into the IUPAC symbols, with ambiguity codes in upper case
+    pub fn decode(&self) -> Vec<u8> {
+        (0..self.len)
+            .rev()
+            .map(|i| SYMBOLS[((self.code >> (i as u32 * IUPAC_ENCODING_LENGTH)) & 0b1111) as usize])
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for IupacBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", str::from_utf8(&self.decode()).unwrap())
+    }
+}
+
+/// Compute the hamming distance between two IupacBits. A position matches when the base sets of
+/// the two sides share at least one base, so `R` matches `A` and `G` but not `C`.
+#[inline]
+pub fn hamming_dist_iupac(alpha: &IupacBits, beta: &IupacBits) -> u32 {
+    let shared = alpha.code & beta.code;
+    // Fold each base's 4 bits down onto its low bit
+    let shared = shared | (shared >> 2);
+    let shared = (shared | (shared >> 1)) & LOW_BITS;
+    alpha.len.max(beta.len) as u32 - shared.count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iupac_two_base_codes() {
+        let r = IupacBits::new(b"R").unwrap();
+        assert_eq!(hamming_dist_iupac(&r, &IupacBits::new(b"A").unwrap()), 0);
+        assert_eq!(hamming_dist_iupac(&r, &IupacBits::new(b"G").unwrap()), 0);
+        assert_eq!(hamming_dist_iupac(&r, &IupacBits::new(b"C").unwrap()), 1);
+        assert_eq!(hamming_dist_iupac(&r, &IupacBits::new(b"Y").unwrap()), 1);
+        assert_eq!(hamming_dist_iupac(&r, &IupacBits::new(b"W").unwrap()), 0);
+
+        let primer = IupacBits::new(b"ACRYWSKM").unwrap();
+        let read = IupacBits::new(b"ACGTAGGC").unwrap();
+        assert_eq!(hamming_dist_iupac(&primer, &read), 0);
+        let read = IupacBits::new(b"ACCAGATA").unwrap();
+        assert_eq!(hamming_dist_iupac(&primer, &read), 4);
+        let read = IupacBits::new(b"NNNNNNNN").unwrap();
+        assert_eq!(hamming_dist_iupac(&primer, &read), 0);
+    }
+
+    #[test]
+    fn test_iupac_round_trip() {
+        let seq = b"ACGTRYSWKMBDHVNA";
+        assert_eq!(seq.len(), MAX_IUPAC_BASES);
+        let bits = IupacBits::new(seq).unwrap();
+        assert_eq!(bits.decode(), seq.to_vec());
+        #[cfg(feature = "std")]
+        assert_eq!(IupacBits::new(b"acgu").unwrap().to_string(), "ACGT");
+        assert!(IupacBits::new(&[b'A'; MAX_IUPAC_BASES + 1]).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Working with the k-mers of sequences longer than a single encoding.
+use crate::{dna_base, len_mask, BaseBits, Bases, ENCODING_LENGTH, MAX_BASES};
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+/// An iterator over the k-mers of a sequence, created with `kmers`. Each k-mer is built from the
+/// last by shifting in the next base and masking off the one that fell out of the window, rather
+/// than re-encoding it.
+#[derive(Debug, Clone)]
+pub struct KmerIter<'a> {
+    /// The bases still to be shifted in
+    rest: &'a [u8],
+    k: usize,
+    code: u64,
+    nbits: u64,
+}
+
+impl<'a> Iterator for KmerIter<'a> {
+    type Item = BaseBits;
+
+    fn next(&mut self) -> Option<BaseBits> {
+        let (&c, rest) = self.rest.split_first()?;
+        self.rest = rest;
+        self.shift_in(c);
+        Some(BaseBits {
+            code: self.code,
+            nbits: self.nbits,
+            len: self.k,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rest.len(), Some(self.rest.len()))
+    }
+}
+
+impl<'a> ExactSizeIterator for KmerIter<'a> {}
+
+impl<'a> KmerIter<'a> {
+    /// Push a base onto the 3' end of the window, dropping the 5' base once the window is full
+    #[inline]
+    fn shift_in(&mut self, c: u8) {
+        let base = dna_base(c);
+        let mask = len_mask(self.k);
+        self.code = ((self.code << ENCODING_LENGTH) | base) & mask;
+        self.nbits = match base {
+            Bases::N => self.nbits << ENCODING_LENGTH,
+            _ => (self.nbits << ENCODING_LENGTH) | 0b111,
+        } | !mask;
+    }
+}
+
+/// Iterate over the `BaseBits` of every window of length `k` in `seq`, 5' to 3'. A `k` of 0, or
+/// above `MAX_BASES` or the length of `seq`, gives an empty iterator.
+pub fn kmers(seq: &[u8], k: usize) -> KmerIter<'_> {
+    let mut iter = KmerIter {
+        rest: &[],
+        k,
+        code: 0,
+        nbits: !0,
+    };
+    if k == 0 || k > MAX_BASES || k > seq.len() {
+        return iter;
+    }
+    for &c in &seq[..k - 1] {
+        iter.shift_in(c);
+    }
+    iter.rest = &seq[k - 1..];
+    iter
+}
+
+/// The set of distinct k-mers in `seq`. With `canonical` each k-mer is replaced by its
+/// `BaseBits::canonical` form so a k-mer and its reverse complement count once. A `k` of 0, or
+/// above `MAX_BASES` or the length of `seq`, gives an empty set.
+#[cfg(feature = "std")]
+pub fn kmer_set(seq: &[u8], k: usize, canonical: bool) -> HashSet<BaseBits> {
+    kmers(seq, k)
+        .map(|bb| if canonical { bb.canonical() } else { bb })
+        .collect()
+}
+
+/// A bottom-`s` MinHash sketch of the canonical k-mers of `seq`: the `s` smallest distinct
+/// `BaseBits::hash_key` values, in ascending order. Fewer than `s` are returned when `seq` has
+/// fewer distinct k-mers.
+#[cfg(feature = "std")]
+pub fn minhash_sketch(seq: &[u8], k: usize, s: usize) -> Vec<u64> {
+    let mut hashes: Vec<u64> = kmer_set(seq, k, true)
+        .iter()
+        .map(BaseBits::hash_key)
+        .collect();
+    hashes.sort_unstable();
+    hashes.truncate(s);
+    hashes
+}
+
+/// Estimate the Jaccard index of two k-mer sets from their `minhash_sketch`es. Both sketches must
+/// be sorted, as `minhash_sketch` returns them, and built with the same `k` and `s`. The estimate
+/// is the fraction of the `s` smallest hashes of the union that appear in both sketches. Two empty
+/// sketches give 0.0.
+pub fn minhash_jaccard(a: &[u64], b: &[u64]) -> f64 {
+    let s = a.len().max(b.len());
+    let (mut i, mut j) = (0, 0);
+    let (mut shared, mut seen) = (0, 0);
+    while seen < s && i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            shared += 1;
+            i += 1;
+            j += 1;
+        } else if a[i] < b[j] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+        seen += 1;
+    }
+    seen += (s - seen).min(a.len() - i + b.len() - j);
+    if seen == 0 {
+        0.0
+    } else {
+        shared as f64 / seen as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_kmers() {
+        let found: Vec<BaseBits> = kmers(b"ACTGA", 4).collect();
+        assert_eq!(
+            found,
+            vec![
+                BaseBits::new(b"ACTG").unwrap(),
+                BaseBits::new(b"CTGA").unwrap()
+            ]
+        );
+
+        // Matches encoding each window from scratch, N's and all
+        let seq = b"ACTGNACTTGCANNGATTACAGATTACAGGCN";
+        for k in 1..=MAX_BASES {
+            let iter = kmers(seq, k);
+            assert_eq!(iter.len(), seq.len() - k + 1);
+            let expected: Vec<BaseBits> =
+                seq.windows(k).map(|w| BaseBits::new(w).unwrap()).collect();
+            assert_eq!(iter.collect::<Vec<_>>(), expected, "k = {}", k);
+        }
+
+        assert_eq!(kmers(b"ACTGA", 0).count(), 0);
+        assert_eq!(kmers(b"ACTGA", 6).count(), 0);
+        assert_eq!(kmers(&[b'A'; 30], MAX_BASES + 1).count(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_kmer_set() {
+        // ACT appears twice
+        let seq = b"ACTGACTA";
+        let windows = seq.len() - 3 + 1;
+        let set = kmer_set(seq, 3, false);
+        assert!(set.len() < windows);
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(&BaseBits::new(b"ACT").unwrap()));
+        assert!(set.contains(&BaseBits::new(b"CTA").unwrap()));
+
+        // AGT is the reverse complement of ACT and TAG of CTA
+        let set = kmer_set(b"ACTAGT", 3, true);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&BaseBits::new(b"ACT").unwrap().canonical()));
+
+        assert!(kmer_set(seq, 0, false).is_empty());
+        assert!(kmer_set(seq, 9, false).is_empty());
+        assert!(kmer_set(&[b'A'; 30], MAX_BASES + 1, false).is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_minhash_jaccard() {
+        // Deterministic pseudo random sequence, the second overlapping the first by half
+        let mut state = 17u64;
+        let seq: Vec<u8> = (0..3000)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                b"ACGT"[(state >> 62) as usize]
+            })
+            .collect();
+        let (a, b) = (&seq[..2000], &seq[1000..]);
+        let k = 15;
+
+        let set_a = kmer_set(a, k, true);
+        let set_b = kmer_set(b, k, true);
+        let exact = set_a.intersection(&set_b).count() as f64 / set_a.union(&set_b).count() as f64;
+
+        let sketch_a = minhash_sketch(a, k, 500);
+        let sketch_b = minhash_sketch(b, k, 500);
+        assert_eq!(sketch_a.len(), 500);
+        assert!(sketch_a.windows(2).all(|w| w[0] < w[1]));
+        let estimate = minhash_jaccard(&sketch_a, &sketch_b);
+        assert!((estimate - exact).abs() < 0.1, "{} vs {}", estimate, exact);
+
+        assert_eq!(minhash_jaccard(&sketch_a, &sketch_a), 1.0);
+        assert_eq!(minhash_jaccard(&[], &[]), 0.0);
+        assert_eq!(minhash_sketch(b"ACGA", 3, 10).len(), 2);
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 /// Encode a DNA string of up to 21 bases as a u64 for fast hamming distance calculations.
 /// Each BaseBits will take up u64 X 2 + usize amount of space. It works by having encodings for A,
 /// C, T, and G that are all dist 2 away from eachother. A sequence is encoded into a u64 by
@@ -8,9 +9,70 @@
 ///
 /// Generally speaking, if you are going to compare against a string more than 4 times, it is worth
 /// the cost of encoding it and using this package.
-use std::fmt;
-use std::str;
-use std::u64;
+#[macro_use]
+extern crate alloc;
+#[cfg(test)]
+extern crate std;
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::ops::Deref;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::{fmt, str};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+mod barcode_set;
+mod bits128;
+mod bitset;
+mod builder;
+mod column;
+mod counter;
+#[cfg(feature = "std")]
+mod delete_index;
+pub mod edit;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod iupac;
+mod kmer;
+mod packed;
+mod scratch;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod stats;
+mod tiebreak;
+
+pub use crate::barcode_set::BarcodeSet;
+pub use crate::bits128::{
+    hamming_dist_nany_128, hamming_dist_none_128, is_member_128, BaseBits128, CONTAINER_WIDTH_128,
+    MAX_BASES_128,
+};
+pub use crate::bitset::{ExactBitsetIndex, MAX_BITSET_BASES};
+pub use crate::builder::BaseBitsBuilder;
+pub use crate::column::{cross_histogram_nany, BaseBitsColumn};
+#[cfg(feature = "std")]
+pub use crate::counter::SeqCounter;
+pub use crate::counter::WildcardCounter;
+#[cfg(feature = "std")]
+pub use crate::delete_index::Delete1Index;
+pub use crate::error::BaseBitsError;
+pub use crate::iupac::{hamming_dist_iupac, IupacBits, IUPAC_ENCODING_LENGTH, MAX_IUPAC_BASES};
+#[cfg(feature = "std")]
+pub use crate::kmer::{kmer_set, minhash_sketch};
+pub use crate::kmer::{kmers, minhash_jaccard, KmerIter};
+pub use crate::packed::{
+    hamming_dist_packed, PackedBits, MAX_PACKED_BASES, PACKED_ENCODING_LENGTH,
+};
+pub use crate::scratch::ScratchPool;
+pub use crate::stats::GcStats;
+pub use crate::tiebreak::TieBreak;
+use crate::tiebreak::TieBreaker;
 
 pub const ENCODING_DIST: u32 = 2;
 pub const ENCODING_LENGTH: u32 = 3;
@@ -19,6 +81,15 @@ pub const MAX_BASES: usize = (CONTAINER_WIDTH / ENCODING_LENGTH) as usize;
 pub const UNDETERMINED: u64 = 0b100;
 //pub const ANY: u64 = 0b111;
 pub const MAX_VAL: u64 = u64::MAX;
+/// The standard genetic code, indexed by codon with bases ordered T, C, A, G
+const CODON_TABLE: &[u8; 64] = b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+/// XOR'ing a base with 0b101 gives its complement (A <-> T, C <-> G), repeated for every group
+const COMPLEMENT_MASK: u64 = 0x5b6d_b6db_6db6_db6d;
+/// The middle bit of every group, set for C (0b110) and G (0b011) but not A, T, or N
+const GC_MASK: u64 = 0x2492_4924_9249_2492;
+/// The top bit of every group. Transitions (A <-> G, C <-> T) XOR to 0b011 and leave it clear,
+/// transversions XOR to 0b110 or 0b101 and set it.
+const TRANSVERSION_MASK: u64 = 0x4924_9249_2492_4924;
 
 struct Bases;
 impl Bases {
@@ -27,10 +98,14 @@ impl Bases {
     const T: u64 = 0b101;
     const G: u64 = 0b011;
     const N: u64 = UNDETERMINED;
+    /// The called bases, in the order neighbors are generated
+    const CALLED: [u64; 4] = [Bases::A, Bases::C, Bases::T, Bases::G];
 }
 
-/// A BaseBits encoding
+/// A BaseBits encoding. The layout is `#[repr(C)]`, with `code` and `nbits` as the first two
+/// words, so a pointer to a BaseBits is also a pointer to its `as_u64_pair`.
 #[derive(Hash, PartialEq, Eq, Debug, Copy, Clone)]
+#[repr(C)]
 pub struct BaseBits {
     /// The u64 holding the encoding
     pub code: u64,
@@ -42,62 +117,798 @@ pub struct BaseBits {
 
 impl BaseBits {
     /// Create a new BaseBits object.
-    pub fn new(seq: &[u8]) -> Result<BaseBits, &'static str> {
+    pub fn new(seq: &[u8]) -> Result<BaseBits, BaseBitsError> {
+        BaseBits::encode(seq, dna_base)
+    }
+
+    /// Create a new BaseBits object without checking the length, for hot loops where lengths
+    /// have already been validated.
+    ///
+    /// # Safety
+    ///
+    /// `seq` must be at most `MAX_BASES` long. Breaking this can't cause memory unsafety, but the
+    /// 5' bases are shifted out of the `u64` and the result is a garbage encoding, one that
+    /// `is_valid` rejects, whose distances are meaningless.
+    #[inline]
+    pub unsafe fn new_unchecked(seq: &[u8]) -> BaseBits {
+        BaseBits::encode_unchecked(seq, dna_base)
+    }
+
+    /// A `const` version of `new`, for building encodings at compile time. See the `bases!`
+    /// macro for the usual way to call it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `seq` is longer than `MAX_BASES`, which fails the build when evaluated in a
+    /// const context.
+    pub const fn new_const(seq: &[u8]) -> BaseBits {
+        assert!(seq.len() <= MAX_BASES, "sequence is longer than MAX_BASES");
         let mut code: u64 = 0;
         let mut nbits: u64 = !0b0;
+        let mut i = 0;
+        while i < seq.len() {
+            let base = dna_base(seq[i]);
+            code = (code << ENCODING_LENGTH) | base;
+            nbits = if base == Bases::N {
+                nbits << ENCODING_LENGTH
+            } else {
+                (nbits << ENCODING_LENGTH) | 0b111
+            };
+            i += 1;
+        }
+        BaseBits {
+            code,
+            nbits,
+            len: seq.len(),
+        }
+    }
+
+    /// Create a new BaseBits object from an RNA sequence. `U` is encoded the same as `T` is by
+    /// `new`, so RNA and DNA encodings can be compared directly. `T` is not part of the RNA
+    /// alphabet and is encoded as an N like any other unrecognized character.
+    pub fn new_rna(seq: &[u8]) -> Result<BaseBits, BaseBitsError> {
+        BaseBits::encode(seq, |c| match c {
+            b'A' => Bases::A,
+            b'C' => Bases::C,
+            b'U' => Bases::T,
+            b'G' => Bases::G,
+            _ => Bases::N,
+        })
+    }
+
+    /// Create a new BaseBits object from either a DNA or an RNA sequence, or a mix of the two. `T`
+    /// and `U` are both encoded as `T`, so a DNA reference and an RNA query compare equal. Decode
+    /// with `decode` or `decode_rna` depending on which alphabet should come back out.
+    pub fn new_nucleic(seq: &[u8]) -> Result<BaseBits, BaseBitsError> {
+        BaseBits::encode(seq, |c| match c {
+            b'U' => Bases::T,
+            _ => dna_base(c),
+        })
+    }
+
+    /// Encode `seq` using `base_of` to pick the encoding of each character
+    #[inline]
+    fn encode(seq: &[u8], base_of: impl Fn(u8) -> u64) -> Result<BaseBits, BaseBitsError> {
         let len = seq.len();
         if len > MAX_BASES {
-            return Err("Length of string to encode exceeds MAX_BASES");
-        }
-        for c in seq.iter() {
-            let base = match c {
-                b'A' => Bases::A,
-                b'C' => Bases::C,
-                b'T' => Bases::T,
-                b'G' => Bases::G,
-                _ => Bases::N,
-            };
+            return Err(BaseBitsError::TooLong {
+                len,
+                max: MAX_BASES,
+            });
+        }
+        Ok(BaseBits::encode_unchecked(seq, base_of))
+    }
+
+    /// Encode `seq` using `base_of`, without checking that it fits
+    #[inline]
+    fn encode_unchecked(seq: &[u8], base_of: impl Fn(u8) -> u64) -> BaseBits {
+        let mut code: u64 = 0;
+        let mut nbits: u64 = !0b0;
+        for &c in seq.iter() {
+            let base = base_of(c);
 
             code = (code << ENCODING_LENGTH) | base;
             nbits = match base {
-                Bases::N => (nbits << ENCODING_LENGTH) | 0b000,
+                Bases::N => nbits << ENCODING_LENGTH,
                 _ => (nbits << ENCODING_LENGTH) | 0b111,
             }
         }
-        Ok(BaseBits { code, nbits, len })
+        BaseBits {
+            code,
+            nbits,
+            len: seq.len(),
+        }
+    }
+
+    /// Create a new BaseBits object, first collapsing IUPAC ambiguity codes to a chosen
+    /// representative base with `representatives`, a list of `(code, base)` pairs such as
+    /// `(b'R', b'A')`. Ambiguity codes without a representative are encoded as N, as in `new`.
+    pub fn new_iupac_collapsed(
+        seq: &[u8],
+        representatives: &[(u8, u8)],
+    ) -> Result<BaseBits, BaseBitsError> {
+        let collapsed: Vec<u8> = seq
+            .iter()
+            .map(|&c| {
+                representatives
+                    .iter()
+                    .find(|&&(code, _)| code == c)
+                    .map_or(c, |&(_, base)| base)
+            })
+            .collect();
+        BaseBits::new(&collapsed)
+    }
+
+    /// Create a new BaseBits object, also returning how many bytes of `seq` were not one of
+    /// `A`, `C`, `T`, `G`, or `N` and were coerced to N.
+    pub fn new_reporting(seq: &[u8]) -> Result<(BaseBits, u32), BaseBitsError> {
+        let bb = BaseBits::new(seq)?;
+        let coerced = seq
+            .iter()
+            .filter(|c| !matches!(c, b'A' | b'C' | b'T' | b'G' | b'N'))
+            .count();
+        Ok((bb, coerced as u32))
+    }
+
+    /// Create a new BaseBits of exactly `target` bases, never erroring. This is lossy: input
+    /// longer than `target` is truncated from the 3' end and shorter input is padded with N's on
+    /// the 3' end. A `target` above `MAX_BASES` is clamped to `MAX_BASES`.
+    pub fn new_clamped(seq: &[u8], target: usize) -> BaseBits {
+        let target = target.min(MAX_BASES);
+        let mut bb = BaseBits::new(&seq[..seq.len().min(target)])
+            .expect("clamped input always fits in MAX_BASES");
+        while bb.len < target {
+            bb.code = (bb.code << ENCODING_LENGTH) | Bases::N;
+            bb.nbits <<= ENCODING_LENGTH;
+            bb.len += 1;
+        }
+        bb
+    }
+
+    /// Create a new BaseBits from a per-position count profile, as produced by `profile`, taking
+    /// the most common base at each position. Ties go to the base that comes first in the
+    /// profile order A, C, T, G, N, so an N is only chosen if it strictly outnumbers every
+    /// called base. Errors if the profile is longer than `MAX_BASES`.
+    pub fn from_profile(profile: &[[u32; 5]]) -> Result<BaseBits, BaseBitsError> {
+        BaseBits::from_profile_with(profile, TieBreak::LowestIndex)
+    }
+
+    /// Like `from_profile`, with ties between called bases resolved by `tie_break` over the
+    /// profile order A, C, T, G. An N is still only chosen if it strictly outnumbers every called
+    /// base.
+    pub fn from_profile_with(
+        profile: &[[u32; 5]],
+        tie_break: TieBreak,
+    ) -> Result<BaseBits, BaseBitsError> {
+        let mut breaker = TieBreaker::new(tie_break);
+        let seq: Vec<u8> = profile
+            .iter()
+            .map(|counts| {
+                let mut best = 0;
+                breaker.reset();
+                for (i, &count) in counts.iter().enumerate().skip(1) {
+                    if count > counts[best] {
+                        best = i;
+                        breaker.reset();
+                    } else if count == counts[best]
+                        && PROFILE_BASES[i] != b'N'
+                        && breaker.replace_on_tie()
+                    {
+                        best = i;
+                    }
+                }
+                PROFILE_BASES[best]
+            })
+            .collect();
+        BaseBits::new(&seq)
+    }
+
+    /// The number of bases encoded
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no bases are encoded
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The N mask. Each base has a 3 bit group, with the 3' base in the lowest bits, that is
+    /// `0b111` for a called base and `0b000` for an N. Bits above the encoded bases are all set.
+    /// Positions cleared here are ignored by `hamming_dist_nany` but counted by
+    /// `hamming_dist_none`, which is why the two can disagree.
+    ///
+    /// ```
+    /// use basebits::BaseBits;
+    ///
+    /// let bb = BaseBits::new(b"ANG").unwrap();
+    /// // G is the lowest group, the N in the middle is cleared
+    /// assert_eq!(bb.nbits() & 0b111_111_111, 0b111_000_111);
+    /// assert_eq!(bb.nbits() >> 9, !0 >> 9);
+    /// ```
+    #[inline]
+    pub fn nbits(&self) -> u64 {
+        self.nbits
+    }
+
+    /// The encoding as `[code, nbits]`, in that order, for loading both words at once in
+    /// hand-written SIMD scans. The order is part of the API and won't change, and matches the
+    /// field order of the `#[repr(C)]` struct.
+    #[inline]
+    pub fn as_u64_pair(&self) -> [u64; 2] {
+        [self.code, self.nbits]
     }
 
     /// Decode a BaseBits object into a string
     pub fn decode(&self) -> Vec<u8> {
-        let mut s = Vec::new();
-        let mut code = self.code;
-        for _ in 0..self.len {
-            let base = extract_bits(code, ENCODING_LENGTH);
-            code = code >> ENCODING_LENGTH;
-            s.push(match base {
-                Bases::A => b'A',
-                Bases::C => b'C',
-                Bases::T => b'T',
-                Bases::G => b'G',
-                _ => b'N',
-            });
+        let mut s = Vec::with_capacity(self.len);
+        self.decode_into(&mut s);
+        s
+    }
+
+    /// Decode into `buf`, replacing its contents. Reusing one buffer across many calls avoids an
+    /// allocation per decode.
+    pub fn decode_into(&self, buf: &mut Vec<u8>) {
+        buf.clear();
+        buf.extend((0..self.len).map(|pos| base_char(self.base_at(pos))));
+    }
+
+    /// Find the logical position (5' -> 3') of the first N, or `None` if there are no N's.
+    pub fn first_n(&self) -> Option<usize> {
+        let ns = !self.nbits & len_mask(self.len);
+        if ns == 0 {
+            return None;
+        }
+        let group = (63 - ns.leading_zeros()) / ENCODING_LENGTH;
+        Some(self.len - 1 - group as usize)
+    }
+
+    /// Copy of self with any trailing (3') run of N's removed. Internal N's are kept.
+    pub fn trim_trailing_n(&self) -> BaseBits {
+        let (_, trailing) = self.terminal_n_runs();
+        let shift = trailing as u32 * ENCODING_LENGTH;
+        BaseBits::masked(
+            self.code.checked_shr(shift).unwrap_or(0),
+            self.nbits.checked_shr(shift).unwrap_or(0),
+            self.len - trailing,
+        )
+    }
+
+    /// Copy of self with any leading (5') run of N's removed. Internal N's are kept.
+    pub fn trim_leading_n(&self) -> BaseBits {
+        let (leading, _) = self.terminal_n_runs();
+        BaseBits::masked(self.code, self.nbits, self.len - leading)
+    }
+
+    /// Copy of self with the bases at the given 0-based positions turned into wildcards, so they
+    /// never contribute to an n-any distance. A masked position is a plain N, with its `nbits`
+    /// cleared and the N code written, so the result is as `new` would have encoded it. Positions
+    /// past the end of the sequence are ignored.
+    pub fn with_mask(&self, positions: &[usize]) -> BaseBits {
+        positions
+            .iter()
+            .filter(|&&pos| pos < self.len)
+            .fold(*self, |bb, &pos| bb.with_base_at(pos, Bases::N))
+    }
+
+    /// Copy of self with the bases at the given sequencing cycles (0-based logical positions from
+    /// the 5' end) set to N, so systematically unreliable cycles are ignored by n-any distances.
+    /// The same as `with_mask`. Cycles past the end of the sequence are ignored.
+    pub fn mask_cycles(&self, bad_cycles: &[usize]) -> BaseBits {
+        self.with_mask(bad_cycles)
+    }
+
+    /// Copy of self with the order of the bases reversed. Bases are not complemented.
+    pub fn reverse(&self) -> BaseBits {
+        let mut code = 0;
+        let mut nbits = 0;
+        for i in 0..self.len as u32 {
+            let shift = i * ENCODING_LENGTH;
+            code = (code << ENCODING_LENGTH) | extract_bits(self.code >> shift, ENCODING_LENGTH);
+            nbits = (nbits << ENCODING_LENGTH) | extract_bits(self.nbits >> shift, ENCODING_LENGTH);
+        }
+        BaseBits::masked(code, nbits, self.len)
+    }
+
+    /// Copy of self reverse complemented. Works directly on the encoding: the bases are reversed
+    /// and each called base is flipped to its complement with an XOR, N's stay N's.
+    pub fn reverse_complement(&self) -> BaseBits {
+        let reversed = self.reverse();
+        BaseBits {
+            code: reversed.code ^ (COMPLEMENT_MASK & reversed.nbits & len_mask(self.len)),
+            ..reversed
+        }
+    }
+
+    /// The canonical form of self, whichever of self and its reverse complement has the smaller
+    /// `code`. A sequence and its reverse complement share a canonical form, so strand doesn't
+    /// matter when comparing them.
+    pub fn canonical(&self) -> BaseBits {
+        let rc = self.reverse_complement();
+        if rc.code < self.code {
+            rc
+        } else {
+            *self
+        }
+    }
+
+    /// A well mixed 64 bit hash of the encoding, stable across runs and platforms unlike
+    /// `std::hash::Hash`. Equal sequences hash equal; the length is mixed in so that a sequence
+    /// and the same bases with leading A's differ.
+    pub fn hash_key(&self) -> u64 {
+        mix64(self.code ^ self.nbits.rotate_left(21) ^ (self.len as u64).rotate_left(42))
+    }
+
+    /// One key per single base deletion of self, SymSpell style, for bucketing near duplicates
+    /// in a plain `HashMap`. The key for deleting position `i` mixes the `hash_key` of what's left
+    /// with `i`, so two sequences whose only difference is a substitution at `i` share that key,
+    /// and sequences at distance 1 always share at least one key. Sequences further apart rarely
+    /// share any, though a hash collision can't be ruled out, so candidates should still be
+    /// checked with a real distance. N's are compared as they are, not as wildcards.
+    pub fn fuzzy_keys(&self) -> Vec<u64> {
+        (0..self.len)
+            .map(|pos| mix64(self.without_base_at(pos).hash_key() ^ pos as u64))
+            .collect()
+    }
+
+    /// Check if self is its own reverse complement, as many restriction sites are
+    pub fn is_palindrome(&self) -> bool {
+        *self == self.reverse_complement()
+    }
+
+    /// Check for the low complexity patterns barcode designs usually forbid: a homopolymer run
+    /// longer than `max_homopolymer` bases, or a dinucleotide repeated more than
+    /// `max_dinuc_repeat` times in a row (`ACACAC` is 3 repeats of `AC`). N's break both kinds of
+    /// run.
+    pub fn is_low_complexity(&self, max_homopolymer: u32, max_dinuc_repeat: u32) -> bool {
+        let (mut homopolymer, mut longest_homopolymer) = (0, 0);
+        let (mut dinuc_span, mut longest_dinuc) = (0, 0);
+        for pos in 0..self.len {
+            if self.is_n_at(pos) {
+                homopolymer = 0;
+                dinuc_span = 0;
+                continue;
+            }
+            let base = self.base_at(pos);
+            let prev = pos
+                .checked_sub(1)
+                .filter(|&p| !self.is_n_at(p))
+                .map(|p| self.base_at(p));
+            if prev == Some(base) {
+                homopolymer += 1;
+                dinuc_span = 0;
+            } else {
+                homopolymer = 1;
+                dinuc_span = match prev {
+                    Some(_) if dinuc_span >= 2 && base == self.base_at(pos - 2) => dinuc_span + 1,
+                    Some(_) => 2,
+                    None => 0,
+                };
+            }
+            longest_homopolymer = longest_homopolymer.max(homopolymer);
+            longest_dinuc = longest_dinuc.max(dinuc_span / 2);
+        }
+        longest_homopolymer > max_homopolymer || longest_dinuc > max_dinuc_repeat
+    }
+
+    /// Translate the sequence to amino acids using the standard codon table, starting at `frame`
+    /// (0, 1, or 2) and dropping any incomplete trailing codon. Stop codons are emitted as `*`
+    /// and codons containing an N as `X`.
+    pub fn translate(&self, frame: usize) -> Vec<u8> {
+        let seq = self.decode();
+        if frame >= seq.len() {
+            return Vec::new();
+        }
+        seq[frame..]
+            .chunks_exact(3)
+            .map(|codon| {
+                let mut idx = 0;
+                for &base in codon {
+                    idx = idx * 4
+                        + match base {
+                            b'T' => 0,
+                            b'C' => 1,
+                            b'A' => 2,
+                            b'G' => 3,
+                            _ => return b'X',
+                        };
+                }
+                CODON_TABLE[idx]
+            })
+            .collect()
+    }
+
+    /// The number of N positions, counted from the `nbits` mask without decoding. Only the
+    /// groups holding bases are looked at, not the unused high bits.
+    pub fn n_count(&self) -> u32 {
+        (!self.nbits & len_mask(self.len)).count_ones() / ENCODING_LENGTH
+    }
+
+    /// The number of G and C bases, counted straight from the encoding: C and G are the only
+    /// bases with the middle bit of their group set.
+    pub fn gc_count(&self) -> u32 {
+        (self.code & GC_MASK).count_ones()
+    }
+
+    /// The fraction of called bases that are G or C. N's are left out of the denominator, and a
+    /// sequence with no called bases has a GC fraction of 0.0.
+    pub fn gc_fraction(&self) -> f64 {
+        match self.len - self.n_count() as usize {
+            0 => 0.0,
+            called => f64::from(self.gc_count()) / called as f64,
+        }
+    }
+
+    /// A cheap `(gc_count, n_count)` summary for pre-filtering comparisons with
+    /// `signature_lower_bound` before paying for a full distance.
+    pub fn signature(&self) -> (u32, u32) {
+        (self.gc_count(), self.n_count())
+    }
+
+    /// The number of called bases that are not A. A is encoded as 0b000 and every other base has
+    /// two bits set, so this is the popcount of `code` halved, skipping N's. Equivalent to the
+    /// n-any distance from the all A sequence of the same length.
+    pub fn hamming_weight(&self) -> u32 {
+        (self.code & self.nbits & len_mask(self.len)).count_ones() / ENCODING_DIST
+    }
+
+    /// Copy of self with any stray bits above the encoded bases cleaned up, `code` bits above
+    /// `len * ENCODING_LENGTH` cleared and the matching `nbits` bits set, as `new` leaves them.
+    /// Values that didn't come from `new` (e.g. built by hand or deserialized) should be
+    /// normalized before comparing, since stray high bits corrupt the distances.
+    pub fn normalized(&self) -> BaseBits {
+        BaseBits::masked(self.code, self.nbits, self.len)
+    }
+
+    /// Check that the encoding is internally consistent, as `new` would have produced it: `len`
+    /// is at most `MAX_BASES`, the bits above the encoded bases are clear in `code` and set in
+    /// `nbits`, every `nbits` group is all 0's (N) or all 1's (called), N groups hold the N code
+    /// and called groups hold one of A, C, T, or G. Useful for checking values built from parts.
+    pub fn is_valid(&self) -> bool {
+        if self.len > MAX_BASES {
+            return false;
+        }
+        let mask = len_mask(self.len);
+        if self.code & !mask != 0 || self.nbits | mask != !0 {
+            return false;
+        }
+        (0..self.len).all(|pos| {
+            let shift = self.shift_of(pos);
+            let base = extract_bits(self.code >> shift, ENCODING_LENGTH);
+            match extract_bits(self.nbits >> shift, ENCODING_LENGTH) {
+                0b000 => base == Bases::N,
+                0b111 => Bases::CALLED.contains(&base),
+                _ => false,
+            }
+        })
+    }
+
+    /// Write self as a FASTA record, `>{id}\n{seq}\n`
+    #[cfg(feature = "std")]
+    pub fn write_fasta<W: Write>(&self, id: &str, w: &mut W) -> io::Result<()> {
+        writeln!(w, ">{}", id)?;
+        w.write_all(&self.decode())?;
+        writeln!(w)
+    }
+
+    /// Check if `other` is within `tol` substitutions of self, counting N's as mismatches
+    /// (`hamming_dist_none`). Reads well in test assertions.
+    pub fn approx_eq(&self, other: &BaseBits, tol: u32) -> bool {
+        hamming_dist_none(self, other) <= tol
+    }
+
+    /// The n-none distance between self and its own reverse complement. A small distance flags a
+    /// near palindrome that may form hairpins.
+    pub fn rc_self_distance(&self) -> u32 {
+        hamming_dist_none(self, &self.reverse_complement())
+    }
+
+    /// Copy of self with each N filled in from the same position in `reference`. Called bases in
+    /// self are kept, and positions that are N in both stay N. Assumes both are the same length.
+    pub fn resolve_against(&self, reference: &BaseBits) -> BaseBits {
+        BaseBits::masked(
+            (self.code & self.nbits) | (reference.code & !self.nbits),
+            self.nbits | reference.nbits,
+            self.len,
+        )
+    }
+
+    /// Copy of self with each N replaced by the most frequent called base at that position in a
+    /// reference panel's `profile`, a best guess at the concrete barcode. Ties go to the base
+    /// that comes first in the order A, C, T, G. N's stay N where the profile has no called bases
+    /// or doesn't reach.
+    pub fn impute_ns(&self, profile: &[[u32; 5]]) -> BaseBits {
+        (0..self.len.min(profile.len()))
+            .filter(|&pos| self.is_n_at(pos))
+            .fold(*self, |bb, pos| {
+                let called = &profile[pos][..4];
+                let mut best = 0;
+                for (i, &count) in called.iter().enumerate() {
+                    if count > called[best] {
+                        best = i;
+                    }
+                }
+                if called[best] == 0 {
+                    bb
+                } else {
+                    bb.with_base_at(pos, Bases::CALLED[best])
+                }
+            })
+    }
+
+    /// Find the offset of the first place `motif` occurs as a contiguous run of bases in self. N's
+    /// match anything, as in `hamming_dist_nany`. Returns `None` if there is no match.
+    pub fn contains_exact(&self, motif: &BaseBits) -> Option<usize> {
+        if motif.len > self.len {
+            return None;
+        }
+        (0..=self.len - motif.len).find(|&start| {
+            let window = self.window(start, motif.len);
+            (window.code ^ motif.code) & window.nbits & motif.nbits == 0
+        })
+    }
+
+    /// The `len` bases starting at logical position `start`
+    #[inline]
+    fn window(&self, start: usize, len: usize) -> BaseBits {
+        let shift = (self.len - start - len) as u32 * ENCODING_LENGTH;
+        BaseBits::masked(self.code >> shift, self.nbits >> shift, len)
+    }
+
+    /// Build a BaseBits from raw parts, clearing any `code` bits above `len` and setting the unused
+    /// `nbits` high bits to match what `new` produces.
+    #[inline]
+    fn masked(code: u64, nbits: u64, len: usize) -> BaseBits {
+        let mask = len_mask(len);
+        BaseBits {
+            code: code & mask,
+            nbits: nbits | !mask,
+            len,
+        }
+    }
+
+    /// Decode a BaseBits object into an RNA string, emitting `U` in place of `T`
+    pub fn decode_rna(&self) -> Vec<u8> {
+        let mut s = self.decode();
+        for c in s.iter_mut().filter(|c| **c == b'T') {
+            *c = b'U';
         }
-        s.into_iter().rev().collect()
+        s
+    }
+
+    /// Check if the base at logical position `pos` (5' -> 3') is an N
+    #[inline]
+    fn is_n_at(&self, pos: usize) -> bool {
+        extract_bits(self.nbits >> self.shift_of(pos), ENCODING_LENGTH) == 0
+    }
+
+    /// Bit offset of the group holding logical position `pos` (5' -> 3')
+    #[inline]
+    fn shift_of(&self, pos: usize) -> u32 {
+        (self.len - 1 - pos) as u32 * ENCODING_LENGTH
+    }
+
+    /// The encoded base at logical position `pos`
+    #[inline]
+    fn base_at(&self, pos: usize) -> u64 {
+        extract_bits(self.code >> self.shift_of(pos), ENCODING_LENGTH)
+    }
+
+    /// Copy of self rotated to start at logical position `offset`, so the first `offset` bases
+    /// move to the 3' end. `offset` must be less than `len`.
+    fn rotated(&self, offset: usize) -> BaseBits {
+        if offset == 0 {
+            return *self;
+        }
+        let mask = len_mask(self.len);
+        let head = offset as u32 * ENCODING_LENGTH;
+        let tail = (self.len - offset) as u32 * ENCODING_LENGTH;
+        let rotate = |bits: u64| ((bits << head) | ((bits & mask) >> tail)) & mask;
+        BaseBits {
+            code: rotate(self.code),
+            nbits: rotate(self.nbits) | !mask,
+            len: self.len,
+        }
+    }
+
+    /// Copy of self with the base at logical position `pos` removed, one base shorter
+    fn without_base_at(&self, pos: usize) -> BaseBits {
+        let shift = self.shift_of(pos);
+        let low = (1 << shift) - 1;
+        let delete = |bits: u64| ((bits >> (shift + ENCODING_LENGTH)) << shift) | (bits & low);
+        BaseBits {
+            code: delete(self.code),
+            nbits: delete(self.nbits) | !len_mask(self.len - 1),
+            len: self.len - 1,
+        }
+    }
+
+    /// Copy of self with the base at logical position `pos` replaced by `base`
+    fn with_base_at(&self, pos: usize, base: u64) -> BaseBits {
+        let shift = self.shift_of(pos);
+        let group = 0b111 << shift;
+        let nbits = match base {
+            Bases::N => self.nbits & !group,
+            _ => self.nbits | group,
+        };
+        BaseBits {
+            code: (self.code & !group) | (base << shift),
+            nbits,
+            len: self.len,
+        }
+    }
+
+    /// Iterate over all sequences a single substitution away from self. Each called base is swapped
+    /// for the three other bases in turn, moving through the positions 5' -> 3' so that
+    /// consecutive neighbors only differ at one position while sharing a substituted position,
+    /// and at the minimum of two positions when moving on to the next one. N positions are
+    /// skipped since substituting them is distance 0 under n-any.
+    pub fn gray_ordered_ball_1(&self) -> impl Iterator<Item = BaseBits> {
+        let bb = *self;
+        (0..bb.len)
+            .filter(move |&pos| !bb.is_n_at(pos))
+            .flat_map(move |pos| {
+                let current = bb.base_at(pos);
+                Bases::CALLED
+                    .iter()
+                    .filter(move |&&base| base != current)
+                    .map(move |&base| bb.with_base_at(pos, base))
+            })
+    }
+
+    /// Lengths of the leading and trailing runs of N's. An all N sequence is reported as entirely
+    /// leading and entirely trailing.
+    fn terminal_n_runs(&self) -> (usize, usize) {
+        let leading = (0..self.len).take_while(|&i| self.is_n_at(i)).count();
+        let trailing = (0..self.len).rev().take_while(|&i| self.is_n_at(i)).count();
+        (leading, trailing)
     }
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for BaseBits {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", str::from_utf8(&self.decode()).unwrap())
     }
 }
 
+/// The rank of each 3 bit base code in ASCII order (A < C < G < N < T), indexed by code
+const LEX_RANK: [u64; 8] = [0, 0, 0, 2, 3, 4, 1, 0];
+
+impl BaseBits {
+    /// A key that orders sequences of the same length by their decoded bases. Each base is
+    /// replaced by its `LEX_RANK`, and the result is left aligned so that a sequence's key is never
+    /// above the key of a longer sequence it is a prefix of.
+    fn lex_key(&self) -> u64 {
+        let ranked = (0..self.len).fold(0, |key, pos| {
+            (key << ENCODING_LENGTH) | LEX_RANK[self.base_at(pos) as usize]
+        });
+        match self.len {
+            0 => 0,
+            len => ranked << (CONTAINER_WIDTH - len as u32 * ENCODING_LENGTH),
+        }
+    }
+}
+
+/// BaseBits are ordered lexicographically by their decoded sequence, the same order as sorting
+/// the `decode`d bytes, with a sequence sorting before any longer sequence it is a prefix of. This
+/// is not the order of the raw `code`s, which puts bases in the order A, G, N, T, C.
+impl Ord for BaseBits {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.lex_key()
+            .cmp(&other.lex_key())
+            .then(self.len.cmp(&other.len))
+            .then(self.code.cmp(&other.code))
+            .then(self.nbits.cmp(&other.nbits))
+    }
+}
+
+impl PartialOrd for BaseBits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TryFrom<&[u8]> for BaseBits {
+    type Error = BaseBitsError;
+
+    fn try_from(seq: &[u8]) -> Result<BaseBits, BaseBitsError> {
+        BaseBits::new(seq)
+    }
+}
+
+/// Parses a string the same way `new` handles bytes. Each non-ASCII character counts as a single
+/// unrecognized character and is encoded as one N, rather than one N per UTF-8 byte.
+impl FromStr for BaseBits {
+    type Err = BaseBitsError;
+
+    fn from_str(s: &str) -> Result<BaseBits, BaseBitsError> {
+        if s.is_ascii() {
+            return BaseBits::new(s.as_bytes());
+        }
+        let seq: Vec<u8> = s
+            .chars()
+            .map(|c| if c.is_ascii() { c as u8 } else { b'N' })
+            .collect();
+        BaseBits::new(&seq)
+    }
+}
+
+/// A BaseBits encoding that is guaranteed to hold at most `CAP` bases. `CAP` must not exceed
+/// `MAX_BASES`, which is checked at compile time. Derefs to `BaseBits` so all the distance
+/// functions can be used on it directly.
+#[derive(Hash, PartialEq, Eq, Debug, Copy, Clone)]
+pub struct BaseBitsCapped<const CAP: usize> {
+    inner: BaseBits,
+}
+
+impl<const CAP: usize> BaseBitsCapped<CAP> {
+    const CAP_FITS: () = assert!(CAP <= MAX_BASES, "CAP exceeds MAX_BASES");
+
+    /// Create a new BaseBitsCapped object, erroring if the sequence is longer than `CAP`.
+    pub fn new(seq: &[u8]) -> Result<BaseBitsCapped<CAP>, BaseBitsError> {
+        let () = Self::CAP_FITS;
+        if seq.len() > CAP {
+            return Err(BaseBitsError::TooLong {
+                len: seq.len(),
+                max: CAP,
+            });
+        }
+        Ok(BaseBitsCapped {
+            inner: BaseBits::new(seq)?,
+        })
+    }
+
+    /// Get the underlying BaseBits
+    pub fn into_inner(self) -> BaseBits {
+        self.inner
+    }
+}
+
+impl<const CAP: usize> Deref for BaseBitsCapped<CAP> {
+    type Target = BaseBits;
+
+    fn deref(&self) -> &BaseBits {
+        &self.inner
+    }
+}
+
+impl<const CAP: usize> From<BaseBitsCapped<CAP>> for BaseBits {
+    fn from(capped: BaseBitsCapped<CAP>) -> BaseBits {
+        capped.inner
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const CAP: usize> fmt::Display for BaseBitsCapped<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
 /// Compute hamming distance between two strings, count N's as any character
 #[inline]
 pub fn hamming_dist_nany(alpha: &BaseBits, beta: &BaseBits) -> u32 {
     ((alpha.code ^ beta.code) & (alpha.nbits & beta.nbits)).count_ones() / ENCODING_DIST
 }
 
+/// Compute the n-any distance from raw `(code, nbits)` pairs, for callers that store encodings
+/// unpacked. The caller is responsible for both having been encoded from the same length.
+#[inline]
+pub fn dist_nany_raw(alpha: (u64, u64), beta: (u64, u64)) -> u32 {
+    ((alpha.0 ^ beta.0) & (alpha.1 & beta.1)).count_ones() / ENCODING_DIST
+}
+
+/// Compute hamming distance between two strings, count N's as any character
+#[deprecated(
+    since = "1.3.0",
+    note = "use `hamming_dist_nany` (N's as wildcards) or `hamming_dist_none` (N's as mismatches)"
+)]
+#[inline]
+pub fn hamming_dist(alpha: &BaseBits, beta: &BaseBits) -> u32 {
+    hamming_dist_nany(alpha, beta)
+}
+
 /// Compute hamming distace but N's as mismatches. An N - N will still count as a mismatch
 #[inline]
 pub fn hamming_dist_none(alpha: &BaseBits, beta: &BaseBits) -> u32 {
@@ -106,63 +917,843 @@ pub fn hamming_dist_none(alpha: &BaseBits, beta: &BaseBits) -> u32 {
         + ((!nbits_and).count_ones() / ENCODING_LENGTH)
 }
 
-/// Extract 'k' bits from the end of a u64 integer
+/// Compute the n-any distance, first checking that both sequences are the same length. The
+/// unchecked `hamming_dist_nany` compares whole words, so a shorter sequence reads as if it were
+/// padded with A's on the 5' end; this returns a `LengthMismatch` error instead.
 #[inline]
-fn extract_bits(n: u64, k: u32) -> u64 {
-    !(!0u64 << k) & n
+pub fn hamming_dist_nany_checked(alpha: &BaseBits, beta: &BaseBits) -> Result<u32, BaseBitsError> {
+    if alpha.len != beta.len {
+        return Err(BaseBitsError::LengthMismatch {
+            expected: alpha.len,
+            found: beta.len,
+        });
+    }
+    Ok(hamming_dist_nany(alpha, beta))
 }
 
-// Hamming distance functions that don't depend on BaseBits types
-pub mod hamming {
-
-    /// If you have encoded a string using hamming codes, this should work
-    #[inline]
-    pub fn hamming_code(alpha: u64, beta: u64) -> u32 {
-        (alpha ^ beta).count_ones() / 2
+/// Compute the n-any distance, returning it only if it is at most `max`.
+#[inline]
+pub fn hamming_dist_within(alpha: &BaseBits, beta: &BaseBits, max: u32) -> Option<u32> {
+    let dist = hamming_dist_nany(alpha, beta);
+    if dist <= max {
+        Some(dist)
+    } else {
+        None
     }
+}
 
-    /// Classical hamming distance on strings. Skips length check and will stop comparing after
-    /// alpha is exhuasted
-    pub fn hamming_str(alpha: &str, beta: &str) -> u32 {
-        // skip length check. will default to up to length of alpha
-        let mut dist = 0;
-        for (a, b) in alpha.chars().zip(beta.chars()) {
-            if a != b {
-                dist += 1
-            }
-        }
-        dist
-    }
+/// Count the mismatches between called bases that are transitions, a purine for a purine (A <-> G)
+/// or a pyrimidine for a pyrimidine (C <-> T). N's match anything, as in `hamming_dist_nany`.
+/// Assumes both sequences are the same length.
+#[inline]
+pub fn transition_dist(alpha: &BaseBits, beta: &BaseBits) -> u32 {
+    hamming_dist_nany(alpha, beta) - transversion_dist(alpha, beta)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::hamming::*;
-    use super::*;
-    #[test]
-    fn test_hamming_str_dist() {
-        assert_eq!(hamming_str("ACTG", "ACTT"), 1);
-        assert_eq!(hamming_str("ACTG", "ACTTT"), 1);
-    }
+/// Count the mismatches between called bases that are transversions, a purine for a pyrimidine
+/// or the reverse. N's match anything, as in `hamming_dist_nany`. Assumes both sequences are the
+/// same length.
+#[inline]
+pub fn transversion_dist(alpha: &BaseBits, beta: &BaseBits) -> u32 {
+    ((alpha.code ^ beta.code) & (alpha.nbits & beta.nbits) & TRANSVERSION_MASK).count_ones()
+}
 
-    #[test]
-    fn test_base_bits() {
-        let alpha = BaseBits::new(b"ACTG").unwrap();
-        let beta = BaseBits::new(b"ACTT").unwrap();
-        assert_eq!(hamming_dist_nany(&alpha, &beta), 1);
+/// Check if the n-none distance is at most `max`. Every N in either sequence is a mismatch under
+/// n-none, so the larger of the two `n_count`s is a floor on the distance; if that alone is over
+/// `max` the full comparison is skipped.
+#[inline]
+pub fn within_dist_none(alpha: &BaseBits, beta: &BaseBits, max: u32) -> bool {
+    if alpha.n_count().max(beta.n_count()) > max {
+        return false;
     }
+    hamming_dist_none(alpha, beta) <= max
+}
 
-    #[test]
-    fn test_single_bases() {
-        // Selfs
-        assert_eq!(
-            hamming_dist_nany(&BaseBits::new(b"A").unwrap(), &BaseBits::new(b"A").unwrap()),
-            0
-        );
-        assert_eq!(
-            hamming_dist_nany(&BaseBits::new(b"G").unwrap(), &BaseBits::new(b"G").unwrap()),
-            0
-        );
+/// A lower bound on the n-any distance between two same length sequences from their
+/// `signature`s. Every G or C in one sequence that isn't opposite a G, C, or N in the other is a
+/// mismatch, and at most `gc_b + n_b` of `alpha`'s G/C's can be covered that way, so the distance
+/// is at least `gc_a - (gc_b + n_b)`, and likewise the other way around. If this bound is already
+/// over a threshold the full distance doesn't need computing.
+#[inline]
+pub fn signature_lower_bound(alpha_sig: (u32, u32), beta_sig: (u32, u32)) -> u32 {
+    let (alpha_gc, alpha_n) = alpha_sig;
+    let (beta_gc, beta_n) = beta_sig;
+    alpha_gc
+        .saturating_sub(beta_gc + beta_n)
+        .max(beta_gc.saturating_sub(alpha_gc + alpha_n))
+}
+
+/// Compute the n-any distance capped at `cap`, along with whether the true distance exceeded the
+/// cap.
+#[inline]
+pub fn dist_nany_saturating(alpha: &BaseBits, beta: &BaseBits, cap: u32) -> (u32, bool) {
+    let dist = hamming_dist_nany(alpha, beta);
+    (dist.min(cap), dist > cap)
+}
+
+/// Check if `candidate` is within `k` mismatches of a cluster `representative`, counting N's as
+/// mismatches (`hamming_dist_none`). A single word is compared in one go; the multi word
+/// `is_member_128` stops as soon as more than `k` mismatches have been found.
+#[inline]
+pub fn is_member(candidate: &BaseBits, representative: &BaseBits, k: u32) -> bool {
+    hamming_dist_none(candidate, representative) <= k
+}
+
+/// Bitmask of the positions where `query` agrees with `consensus` under n-any, with bit `i` set
+/// if logical position `i` (5' -> 3') matches. Assumes both are the same length.
+pub fn agreement_mask(query: &BaseBits, consensus: &BaseBits) -> u32 {
+    let diff = (query.code ^ consensus.code) & query.nbits & consensus.nbits;
+    (0..query.len)
+        .filter(|&pos| extract_bits(diff >> query.shift_of(pos), ENCODING_LENGTH) == 0)
+        .fold(0, |mask, pos| mask | 1 << pos)
+}
+
+/// Count the positions where `pred` holds for the pair of bases, given as the characters `decode`
+/// would write (`A`, `C`, `G`, `T`, or `N`) with `alpha`'s base first. This covers any per-position
+/// condition, such as both bases being purines, that the fixed distances don't. Assumes both are
+/// the same length.
+pub fn count_positions_where(
+    alpha: &BaseBits,
+    beta: &BaseBits,
+    pred: impl Fn(u8, u8) -> bool,
+) -> u32 {
+    (0..alpha.len)
+        .filter(|&pos| pred(base_char(alpha.base_at(pos)), base_char(beta.base_at(pos))))
+        .count() as u32
+}
+
+/// Decide if two barcodes could have come from the same molecule given at most `max_subs`
+/// substitutions. With `allow_n_wildcards` N's match anything (`hamming_dist_nany`), otherwise
+/// they count against the budget (`hamming_dist_none`).
+#[inline]
+pub fn could_be_same(
+    alpha: &BaseBits,
+    beta: &BaseBits,
+    max_subs: u32,
+    allow_n_wildcards: bool,
+) -> bool {
+    let dist = if allow_n_wildcards {
+        hamming_dist_nany(alpha, beta)
+    } else {
+        hamming_dist_none(alpha, beta)
+    };
+    dist <= max_subs
+}
+
+/// Compute the n-any distance over positions `[start, start + len)` of two sequences, without
+/// extracting the windows first.
+///
+/// # Panics
+///
+/// Panics if the window runs past the end of either sequence.
+pub fn windowed_dist_nany(alpha: &BaseBits, beta: &BaseBits, start: usize, len: usize) -> u32 {
+    assert!(
+        start + len <= alpha.len && start + len <= beta.len,
+        "window exceeds sequence length"
+    );
+    hamming_dist_nany(&alpha.window(start, len), &beta.window(start, len))
+}
+
+/// Find the phasing shift that best aligns `beta` to `alpha`. A shift of `s` compares `beta[i]`
+/// with `alpha[i + s]` over the region where the two overlap, so a positive shift means `beta`
+/// is running ahead of `alpha`. Every shift in `[-max_shift, max_shift]` that leaves some overlap
+/// is tried, and the shift and n-any distance with the smallest distance are returned, preferring
+/// the smallest absolute shift (then the negative one) on ties. Assumes both sequences are the
+/// same length.
+pub fn best_phase_dist_nany(alpha: &BaseBits, beta: &BaseBits, max_shift: usize) -> (i32, u32) {
+    let len = alpha.len.min(beta.len);
+    let max_shift = max_shift.min(len.saturating_sub(1));
+    let mut best = (0, windowed_dist_nany(alpha, beta, 0, len));
+    for shift in 1..=max_shift {
+        let overlap = len - shift;
+        let ahead = hamming_dist_nany(&alpha.window(shift, overlap), &beta.window(0, overlap));
+        let behind = hamming_dist_nany(&alpha.window(0, overlap), &beta.window(shift, overlap));
+        if behind < best.1 {
+            best = (-(shift as i32), behind);
+        }
+        if ahead < best.1 {
+            best = (shift as i32, ahead);
+        }
+    }
+    best
+}
+
+/// Find the rotation of `circular` that best matches `fixed` under n-any. Rotation `r` starts
+/// `circular` at its base `r` and wraps around, and the offset and distance of the closest
+/// rotation are returned, preferring the smallest offset on ties. Assumes both sequences are the
+/// same length.
+pub fn circular_dist_nany(fixed: &BaseBits, circular: &BaseBits) -> (usize, u32) {
+    let mut best = (0, hamming_dist_nany(fixed, circular));
+    for offset in 1..circular.len {
+        let dist = hamming_dist_nany(fixed, &circular.rotated(offset));
+        if dist < best.1 {
+            best = (offset, dist);
+        }
+    }
+    best
+}
+
+/// Compute the n-any distance between two strings without encoding them up front. N's (and any
+/// other unrecognized characters) in either string match anything, unlike `hamming::hamming_str`
+/// which compares characters literally. Handy for one-off comparisons where the cost of
+/// encoding doesn't matter. Errors if either string is too long to encode.
+pub fn encoded_str_dist_nany(alpha: &str, beta: &str) -> Result<u32, BaseBitsError> {
+    Ok(hamming_dist_nany(
+        &BaseBits::new(alpha.as_bytes())?,
+        &BaseBits::new(beta.as_bytes())?,
+    ))
+}
+
+/// Compute the n-any distance between every query and every reference. Row `i` of the result holds
+/// the distances from `queries[i]` to each of `refs`, in order. All sequences are assumed to be
+/// the same length.
+pub fn cross_dist_nany(queries: &[BaseBits], refs: &[BaseBits]) -> Vec<Vec<u32>> {
+    queries
+        .iter()
+        .map(|query| hamming_dist_nany_batch(query, refs))
+        .collect()
+}
+
+/// Lazily compute the n-any distance from `query` to each reference, in order.
+pub fn distances_nany<'a>(
+    query: &'a BaseBits,
+    refs: impl Iterator<Item = &'a BaseBits> + 'a,
+) -> impl Iterator<Item = u32> + 'a {
+    refs.map(move |r| hamming_dist_nany(query, r))
+}
+
+/// The mean n-any distance from `query` to each reference. Returns NaN if `refs` is empty, since
+/// there is no meaningful mean to report.
+pub fn mean_dist_nany(query: &BaseBits, refs: &[BaseBits]) -> f64 {
+    let total: u64 = distances_nany(query, refs.iter()).map(u64::from).sum();
+    total as f64 / refs.len() as f64
+}
+
+/// Compute the n-any distance from `query` to each reference and write them to `w` as little
+/// endian `u32`'s, in reference order, without collecting them first. Wrap `w` in a `BufWriter`
+/// if it isn't buffered already.
+#[cfg(feature = "std")]
+pub fn dist_nany_batch_to_writer<W: Write>(
+    query: &BaseBits,
+    refs: &[BaseBits],
+    w: &mut W,
+) -> io::Result<()> {
+    for dist in distances_nany(query, refs.iter()) {
+        w.write_all(&dist.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Encode each `(start, len)` window of `read` and compute the n-any distance between every pair
+/// of windows. Entry `[i][j]` is the distance between windows `i` and `j`. The windows should
+/// all be the same length. Errors if a window is longer than `MAX_BASES`.
+///
+/// # Panics
+///
+/// Panics if a window runs past the end of `read`.
+pub fn window_pairwise_nany(
+    read: &[u8],
+    windows: &[(usize, usize)],
+) -> Result<Vec<Vec<u32>>, BaseBitsError> {
+    let encoded = windows
+        .iter()
+        .map(|&(start, len)| BaseBits::new(&read[start..start + len]))
+        .collect::<Result<Vec<BaseBits>, BaseBitsError>>()?;
+    Ok(cross_dist_nany(&encoded, &encoded))
+}
+
+/// Find the first reference exactly equal to `query`, returning as soon as one is found. Unlike a
+/// distance of 0 under n-any, an N only matches another N at the same position.
+pub fn find_exact(query: &BaseBits, refs: &[BaseBits]) -> Option<usize> {
+    refs.iter().position(|r| r == query)
+}
+
+/// Find the reference closest to `query` under n-any. Returns the index of the reference and its
+/// distance, preferring the lowest index on ties, or `None` if `refs` is empty.
+pub fn nearest_nany(query: &BaseBits, refs: &[BaseBits]) -> Option<(usize, u32)> {
+    nearest_nany_with(query, refs, TieBreak::LowestIndex)
+}
+
+/// Like `nearest_nany`, with ties between equally close references resolved by `tie_break`.
+pub fn nearest_nany_with(
+    query: &BaseBits,
+    refs: &[BaseBits],
+    tie_break: TieBreak,
+) -> Option<(usize, u32)> {
+    let mut breaker = TieBreaker::new(tie_break);
+    let mut best: Option<(usize, u32)> = None;
+    for (i, r) in refs.iter().enumerate() {
+        let dist = hamming_dist_nany(query, r);
+        match best {
+            Some((_, best_dist)) if best_dist < dist => (),
+            Some((_, best_dist)) if best_dist == dist => {
+                if breaker.replace_on_tie() {
+                    best = Some((i, dist));
+                }
+            }
+            _ => {
+                best = Some((i, dist));
+                breaker.reset();
+            }
+        }
+    }
+    best
+}
+
+/// Assign each query to its closest reference under n-any, returning `(ref_index, distance)` for
+/// each query in query order. Ties go to the lowest reference index. If `refs` is empty there is
+/// nothing to assign to and the result is empty.
+pub fn assign_all_nany(queries: &[BaseBits], refs: &[BaseBits]) -> Vec<(usize, u32)> {
+    let mut row = vec![0; refs.len()];
+    queries
+        .iter()
+        .filter_map(|query| assign_nany_into(query, refs, &mut row))
+        .collect()
+}
+
+/// Parallel version of `assign_all_nany`, splitting the queries across the rayon thread pool.
+/// Results are returned in query order.
+#[cfg(feature = "rayon")]
+pub fn par_assign_all_nany(queries: &[BaseBits], refs: &[BaseBits]) -> Vec<(usize, u32)> {
+    queries
+        .par_iter()
+        .map_init(
+            || vec![0; refs.len()],
+            |row, query| assign_nany_into(query, refs, row),
+        )
+        .flatten()
+        .collect()
+}
+
+/// Fill `row` with the batch distances from `query` to `refs` and return the closest reference,
+/// preferring the lowest index on ties
+#[inline]
+fn assign_nany_into(query: &BaseBits, refs: &[BaseBits], row: &mut [u32]) -> Option<(usize, u32)> {
+    hamming_dist_nany_batch_into(query, refs, row);
+    row.iter()
+        .copied()
+        .enumerate()
+        .min_by_key(|&(_, dist)| dist)
+}
+
+/// The full pairwise n-any distance matrix of `seqs`, where row `i` holds the distances from
+/// `seqs[i]` to every sequence. With the `rayon` feature the rows are filled in parallel across
+/// the rayon thread pool, otherwise one after another; the result is the same either way.
+pub fn pairwise_matrix(seqs: &[BaseBits]) -> Vec<Vec<u32>> {
+    #[cfg(feature = "rayon")]
+    let rows = seqs.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let rows = seqs.iter();
+    rows.map(|seq| hamming_dist_nany_batch(seq, seqs)).collect()
+}
+
+/// Compute the n-any distance between every distinct pair in `seqs`, calling `sink(i, j, dist)`
+/// once per pair with `i < j`. Pairs are visited block by block, `block` sequences at a time, so
+/// that both blocks being compared stay in cache for large self-joins. Nothing is materialized,
+/// it's up to `sink` to keep what it needs. A `block` of 0 is treated as 1.
+pub fn blocked_self_dist_nany(
+    seqs: &[BaseBits],
+    block: usize,
+    mut sink: impl FnMut(usize, usize, u32),
+) {
+    let block = block.max(1);
+    for row_start in (0..seqs.len()).step_by(block) {
+        let row_end = (row_start + block).min(seqs.len());
+        for col_start in (row_start..seqs.len()).step_by(block) {
+            let col_end = (col_start + block).min(seqs.len());
+            for i in row_start..row_end {
+                for j in col_start.max(i + 1)..col_end {
+                    sink(i, j, hamming_dist_nany(&seqs[i], &seqs[j]));
+                }
+            }
+        }
+    }
+}
+
+/// Collapse runs of identical consecutive barcodes into `(barcode, count)` pairs. On a sorted stream
+/// this counts every distinct barcode without needing a `HashMap`. Equality is exact, N's are
+/// not treated as wildcards.
+pub fn group_runs(iter: impl Iterator<Item = BaseBits>) -> impl Iterator<Item = (BaseBits, u64)> {
+    GroupRuns {
+        iter: iter.peekable(),
+    }
+}
+
+struct GroupRuns<I: Iterator<Item = BaseBits>> {
+    iter: core::iter::Peekable<I>,
+}
+
+impl<I: Iterator<Item = BaseBits>> Iterator for GroupRuns<I> {
+    type Item = (BaseBits, u64);
+
+    fn next(&mut self) -> Option<(BaseBits, u64)> {
+        let current = self.iter.next()?;
+        let mut count = 1;
+        while self.iter.next_if_eq(&current).is_some() {
+            count += 1;
+        }
+        Some((current, count))
+    }
+}
+
+/// Remove every sequence containing an N, keeping the rest in order.
+pub fn retain_n_free(seqs: &mut Vec<BaseBits>) {
+    seqs.retain(|bb| bb.n_count() == 0)
+}
+
+/// The order bases are tallied in by `profile`
+pub const PROFILE_BASES: [u8; 5] = [b'A', b'C', b'T', b'G', b'N'];
+
+/// Tally the bases seen at each position across `seqs`, in `PROFILE_BASES` order (A, C, T, G,
+/// N). All sequences are assumed to be the same length as the first one.
+pub fn profile(seqs: &[BaseBits]) -> Vec<[u32; 5]> {
+    let len = seqs.first().map_or(0, |bb| bb.len);
+    let mut counts = vec![[0; 5]; len];
+    for bb in seqs {
+        for (pos, tally) in counts.iter_mut().enumerate() {
+            let idx = match bb.base_at(pos) {
+                Bases::A => 0,
+                Bases::C => 1,
+                Bases::T => 2,
+                Bases::G => 3,
+                _ => 4,
+            };
+            tally[idx] += 1;
+        }
+    }
+    counts
+}
+
+/// The majority base at each position across `seqs`, the `BaseBits::from_profile` of their
+/// `profile`. Ties go to the base that comes first in the order A, C, T, G.
+pub fn consensus(seqs: &[BaseBits]) -> BaseBits {
+    consensus_with(seqs, TieBreak::LowestIndex)
+}
+
+/// Like `consensus`, with ties between equally common bases resolved by `tie_break` over the
+/// order A, C, T, G.
+pub fn consensus_with(seqs: &[BaseBits], tie_break: TieBreak) -> BaseBits {
+    BaseBits::from_profile_with(&profile(seqs), tie_break)
+        .expect("profile of BaseBits is at most MAX_BASES long")
+}
+
+/// The Shannon entropy, in bits, of the called bases at each position of a `profile`. N's are left
+/// out, and a position with no called bases has an entropy of 0.0. Positions where the references
+/// vary a lot score close to the maximum of 2.0 and are the most informative for telling them
+/// apart.
+#[cfg(feature = "std")]
+pub fn profile_entropy(profile: &[[u32; 5]]) -> Vec<f64> {
+    profile
+        .iter()
+        .map(|counts| {
+            let called: u32 = counts[..4].iter().sum();
+            counts[..4]
+                .iter()
+                .filter(|&&count| count > 0)
+                .map(|&count| {
+                    let p = f64::from(count) / f64::from(called);
+                    -p * p.log2()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// The number of substitution errors that are guaranteed to be correctable for a whitelist,
+/// `floor((d - 1) / 2)` where `d` is the smallest n-any distance between any two members. A
+/// whitelist with duplicate members can't correct anything and returns 0. Returns `None` if
+/// there are fewer than two sequences.
+pub fn correctable_errors_nany(seqs: &[BaseBits]) -> Option<u32> {
+    if seqs.len() < 2 {
+        return None;
+    }
+    let mut min_dist = u32::MAX;
+    blocked_self_dist_nany(seqs, 64, |_, _, dist| min_dist = min_dist.min(dist));
+    Some(min_dist.saturating_sub(1) / 2)
+}
+
+/// Write the per-position base frequencies of `seqs` as a TSV for plotting a sequence logo. The
+/// header is `pos` followed by the `PROFILE_BASES`, then there is one row per (0-based) position
+/// giving the fraction of sequences with each base there, to 4 decimal places.
+#[cfg(feature = "std")]
+pub fn write_logo_tsv<W: Write>(seqs: &[BaseBits], w: &mut W) -> io::Result<()> {
+    writeln!(w, "pos\tA\tC\tT\tG\tN")?;
+    for (pos, counts) in profile(seqs).iter().enumerate() {
+        write!(w, "{}", pos)?;
+        for &count in counts {
+            write!(w, "\t{:.4}", f64::from(count) / seqs.len() as f64)?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Lazily compute the n-any distance between each barcode in a stream and the one before it,
+/// yielding one fewer item than the stream. Large jumps in an ordered stream mark change points.
+pub fn consecutive_dists_nany(iter: impl Iterator<Item = BaseBits>) -> impl Iterator<Item = u32> {
+    let mut prev: Option<BaseBits> = None;
+    iter.filter_map(move |bb| {
+        let dist = prev.map(|p| hamming_dist_nany(&p, &bb));
+        prev = Some(bb);
+        dist
+    })
+}
+
+/// Build a k-nearest-neighbor graph over `seqs` under n-any. Entry `i` lists the (up to) `k`
+/// other sequences closest to `seqs[i]` as `(index, distance)`, sorted by distance and then by
+/// index.
+pub fn knn_graph_nany(seqs: &[BaseBits], k: usize) -> Vec<Vec<(usize, u32)>> {
+    seqs.iter()
+        .enumerate()
+        .map(|(i, query)| {
+            let mut neighbors: Vec<(usize, u32)> = distances_nany(query, seqs.iter())
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .collect();
+            neighbors.sort_by_key(|&(j, dist)| (dist, j));
+            neighbors.truncate(k);
+            neighbors
+        })
+        .collect()
+}
+
+/// Find the medoid of a cluster, the member with the smallest total n-any distance to all other
+/// members. Returns its index, preferring the lowest index on ties, or `None` if `seqs` is empty.
+pub fn medoid_nany(seqs: &[BaseBits]) -> Option<usize> {
+    medoid_nany_with(seqs, TieBreak::LowestIndex)
+}
+
+/// Like `medoid_nany`, with ties between members of equal total distance resolved by
+/// `tie_break`.
+pub fn medoid_nany_with(seqs: &[BaseBits], tie_break: TieBreak) -> Option<usize> {
+    let mut breaker = TieBreaker::new(tie_break);
+    let mut best: Option<(usize, u64)> = None;
+    let mut row = vec![0; seqs.len()];
+    for (i, seq) in seqs.iter().enumerate() {
+        hamming_dist_nany_batch_into(seq, seqs, &mut row);
+        let total = row.iter().map(|&d| u64::from(d)).sum::<u64>();
+        match best {
+            Some((_, best_total)) if best_total < total => (),
+            Some((_, best_total)) if best_total == total => {
+                if breaker.replace_on_tie() {
+                    best = Some((i, total));
+                }
+            }
+            _ => {
+                best = Some((i, total));
+                breaker.reset();
+            }
+        }
+    }
+    best.map(|(i, _)| i)
+}
+
+/// Suggest a maximum distance for barcode correction. Each query's nearest reference distance is
+/// tallied into a histogram, which is typically bimodal with true matches near 0 and noise further
+/// out. The split is chosen with Otsu's method, maximizing the between-class variance, and
+/// queries at or below the returned distance can be treated as matches. Ties go to the smallest
+/// threshold. Returns 0 if there is nothing to assign.
+pub fn suggest_correction_threshold(queries: &[BaseBits], refs: &[BaseBits]) -> u32 {
+    let mut hist: Vec<u64> = vec![0; MAX_BASES + 1];
+    for (_, dist) in assign_all_nany(queries, refs) {
+        // Stray bits set above the bases, say through `code`, can push a distance past MAX_BASES
+        hist[dist.min(MAX_BASES as u32) as usize] += 1;
+    }
+    otsu_threshold(&hist)
+}
+
+/// Otsu's threshold over a histogram, returning the last bin of the lower class
+fn otsu_threshold(hist: &[u64]) -> u32 {
+    let total: u64 = hist.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let total_sum: f64 = hist
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| (i as u64 * c) as f64)
+        .sum();
+
+    let mut best = (0, f64::MIN);
+    let mut lower_count = 0;
+    let mut lower_sum = 0.0;
+    for (t, &count) in hist.iter().enumerate() {
+        lower_count += count;
+        lower_sum += (t as u64 * count) as f64;
+        let upper_count = total - lower_count;
+        if lower_count == 0 || upper_count == 0 {
+            continue;
+        }
+        let lower_mean = lower_sum / lower_count as f64;
+        let upper_mean = (total_sum - lower_sum) / upper_count as f64;
+        let gap = lower_mean - upper_mean;
+        let variance = lower_count as f64 * upper_count as f64 * gap * gap;
+        if variance > best.1 {
+            best = (t as u32, variance);
+        }
+    }
+    best.0
+}
+
+/// Compute the n-any distance from `query` to each reference. Gives the same results as calling
+/// `hamming_dist_nany` in a loop, but the loop is laid out so the compiler can vectorize the
+/// XOR/popcount across references.
+pub fn hamming_dist_nany_batch(query: &BaseBits, refs: &[BaseBits]) -> Vec<u32> {
+    let mut out = vec![0; refs.len()];
+    hamming_dist_nany_batch_into(query, refs, &mut out);
+    out
+}
+
+/// Same as `hamming_dist_nany_batch`, writing the distances into `out` instead of allocating.
+///
+/// # Panics
+///
+/// Panics if `out` is not the same length as `refs`.
+pub fn hamming_dist_nany_batch_into(query: &BaseBits, refs: &[BaseBits], out: &mut [u32]) {
+    assert_eq!(refs.len(), out.len(), "out must be the same length as refs");
+    let (code, nbits) = (query.code, query.nbits);
+    for (dist, r) in out.iter_mut().zip(refs.iter()) {
+        *dist = ((code ^ r.code) & (nbits & r.nbits)).count_ones() / ENCODING_DIST;
+    }
+}
+
+/// Compute a read vs read distance where each mismatch is down-weighted by how likely either read
+/// is to have miscalled that base. `qa` and `qb` are the Phred scores of each base (raw scores,
+/// not offset by 33). A mismatch contributes `1 - (p_a + p_b)`, floored at 0, where
+/// `p = 10^(-q / 10)` is the error probability of a base, so confident mismatches count nearly 1
+/// and mismatches between low quality calls count for little. N's match anything.
+///
+/// # Panics
+///
+/// Panics if `alpha` and `beta` differ in length, or if the quality slices don't match the
+/// lengths of their sequences.
+#[cfg(feature = "std")]
+pub fn phred_weighted_dist(alpha: &BaseBits, qa: &[u8], beta: &BaseBits, qb: &[u8]) -> f64 {
+    assert_eq!(alpha.len, beta.len, "alpha and beta lengths differ");
+    assert_eq!(alpha.len, qa.len(), "alpha and qa lengths differ");
+    assert_eq!(beta.len, qb.len(), "beta and qb lengths differ");
+    let error_prob = |q: u8| 10f64.powf(-f64::from(q) / 10.0);
+    mismatch_positions(alpha, beta)
+        .into_iter()
+        .map(|pos| (1.0 - (error_prob(qa[pos]) + error_prob(qb[pos]))).max(0.0))
+        .sum()
+}
+
+/// Compute a distance where N's are weighted by where they fall in the sequence. Any position that
+/// is an N in either sequence contributes `end_weight` if that N is part of a leading or trailing
+/// run of N's (read-through), and `internal_weight` otherwise (low quality cycles). Mismatches
+/// between called bases count as 1.0. Assumes both sequences are the same length.
+pub fn dist_positional_n(
+    alpha: &BaseBits,
+    beta: &BaseBits,
+    end_weight: f32,
+    internal_weight: f32,
+) -> f32 {
+    let mut dist = hamming_dist_nany(alpha, beta) as f32;
+    let (alpha_lead, alpha_trail) = alpha.terminal_n_runs();
+    let (beta_lead, beta_trail) = beta.terminal_n_runs();
+    for pos in 0..alpha.len {
+        let alpha_n = alpha.is_n_at(pos);
+        let beta_n = beta.is_n_at(pos);
+        if !alpha_n && !beta_n {
+            continue;
+        }
+        let alpha_end = pos < alpha_lead || pos >= alpha.len - alpha_trail;
+        let beta_end = pos < beta_lead || pos >= beta.len - beta_trail;
+        if (alpha_n && alpha_end) || (beta_n && beta_end) {
+            dist += end_weight;
+        } else {
+            dist += internal_weight;
+        }
+    }
+    dist
+}
+
+/// The positions, 0-based from the 5' end, where `alpha` and `beta` differ under n-any. Positions
+/// where either side is an N are never listed. Assumes both sequences are the same length.
+pub fn mismatch_positions(alpha: &BaseBits, beta: &BaseBits) -> Vec<usize> {
+    let diff = (alpha.code ^ beta.code) & (alpha.nbits & beta.nbits);
+    (0..alpha.len)
+        .filter(|&pos| extract_bits(diff >> alpha.shift_of(pos), ENCODING_LENGTH) != 0)
+        .collect()
+}
+
+/// Compute an n-any distance where each mismatch counts the entropy of its position, such as from
+/// `profile_entropy` over a reference set, so mismatches at informative positions weigh more.
+/// Assumes both sequences are the same length.
+///
+/// # Panics
+///
+/// Panics if `position_entropy` has fewer entries than `alpha` has positions.
+pub fn entropy_weighted_dist(alpha: &BaseBits, beta: &BaseBits, position_entropy: &[f64]) -> f64 {
+    assert!(
+        position_entropy.len() >= alpha.len,
+        "position_entropy is shorter than alpha"
+    );
+    mismatch_positions(alpha, beta)
+        .into_iter()
+        .map(|pos| position_entropy[pos])
+        .sum()
+}
+
+/// Encode a string literal as a `BaseBits` constant, checked at compile time. The literal must
+/// be at most `MAX_BASES` long and only hold `A`, `C`, `G`, `T`, or `N`, otherwise the build
+/// fails.
+///
+/// ```
+/// use basebits::{bases, BaseBits};
+///
+/// const LINKER: BaseBits = bases!("ACTGN");
+/// assert_eq!(LINKER, BaseBits::new(b"ACTGN").unwrap());
+/// ```
+///
+/// ```compile_fail
+/// let bad = basebits::bases!("ACTGX");
+/// ```
+#[macro_export]
+macro_rules! bases {
+    ($seq:literal) => {{
+        const BASES: $crate::BaseBits = {
+            let seq = $seq.as_bytes();
+            let mut i = 0;
+            while i < seq.len() {
+                match seq[i] {
+                    b'A' | b'C' | b'G' | b'T' | b'N' => (),
+                    _ => panic!("bases! literals may only hold A, C, G, T, or N"),
+                }
+                i += 1;
+            }
+            $crate::BaseBits::new_const(seq)
+        };
+        BASES
+    }};
+}
+
+/// Mask covering the bits used by `len` encoded bases
+#[inline]
+fn len_mask(len: usize) -> u64 {
+    match len {
+        0 => 0,
+        _ => !0u64 >> (CONTAINER_WIDTH - len as u32 * ENCODING_LENGTH),
+    }
+}
+
+/// The splitmix64 finalizer, spreading every input bit across the output
+#[inline]
+fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// The DNA character for an encoded base, as written by `BaseBits::decode`
+#[inline]
+fn base_char(base: u64) -> u8 {
+    match base {
+        Bases::A => b'A',
+        Bases::C => b'C',
+        Bases::T => b'T',
+        Bases::G => b'G',
+        _ => b'N',
+    }
+}
+
+/// The encoding of a DNA character as read by `BaseBits::new`
+#[inline]
+const fn dna_base(c: u8) -> u64 {
+    match c {
+        b'A' => Bases::A,
+        b'C' => Bases::C,
+        b'T' => Bases::T,
+        b'G' => Bases::G,
+        _ => Bases::N,
+    }
+}
+
+/// Extract 'k' bits from the end of a u64 integer
+#[inline]
+fn extract_bits(n: u64, k: u32) -> u64 {
+    !(!0u64 << k) & n
+}
+
+// Hamming distance functions that don't depend on BaseBits types
+pub mod hamming {
+
+    /// If you have encoded a string using hamming codes, this should work
+    #[inline]
+    pub fn hamming_code(alpha: u64, beta: u64) -> u32 {
+        (alpha ^ beta).count_ones() / 2
+    }
+
+    /// Classical hamming distance on strings. Skips length check and will stop comparing after
+    /// alpha is exhuasted
+    pub fn hamming_str(alpha: &str, beta: &str) -> u32 {
+        // skip length check. will default to up to length of alpha
+        let mut dist = 0;
+        for (a, b) in alpha.chars().zip(beta.chars()) {
+            if a != b {
+                dist += 1
+            }
+        }
+        dist
+    }
+}
+
+/// Re-exports of the commonly used types and functions.
+///
+/// ```
+/// use basebits::prelude::*;
+///
+/// let alpha = BaseBits::new(b"ACTGN").unwrap();
+/// let beta = BaseBits::new(b"ACTTA").unwrap();
+/// assert_eq!(hamming_dist_nany(&alpha, &beta), 1);
+/// assert_eq!(hamming_dist_none(&alpha, &beta), 2);
+/// assert_eq!(hamming_str("ACTGN", "ACTTA"), 2);
+/// ```
+pub mod prelude {
+    pub use crate::hamming::{hamming_code, hamming_str};
+    #[cfg(feature = "rayon")]
+    pub use crate::par_assign_all_nany;
+    pub use crate::{
+        assign_all_nany, best_phase_dist_nany, circular_dist_nany, cross_dist_nany, dist_nany_raw,
+        dist_nany_saturating, dist_positional_n, distances_nany, encoded_str_dist_nany,
+        hamming_dist_iupac, hamming_dist_nany, hamming_dist_nany_128, hamming_dist_nany_batch,
+        hamming_dist_nany_batch_into, hamming_dist_nany_checked, hamming_dist_none,
+        hamming_dist_none_128, hamming_dist_packed, hamming_dist_within, nearest_nany,
+        nearest_nany_with, pairwise_matrix, transition_dist, transversion_dist, windowed_dist_nany,
+        within_dist_none, BarcodeSet, BaseBits, BaseBits128, BaseBitsCapped, BaseBitsColumn,
+        BaseBitsError, IupacBits, PackedBits, TieBreak,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hamming::*;
+    use super::*;
+    #[test]
+    fn test_hamming_str_dist() {
+        assert_eq!(hamming_str("ACTG", "ACTT"), 1);
+        assert_eq!(hamming_str("ACTG", "ACTTT"), 1);
+    }
+
+    #[test]
+    fn test_base_bits() {
+        let alpha = BaseBits::new(b"ACTG").unwrap();
+        let beta = BaseBits::new(b"ACTT").unwrap();
+        assert_eq!(hamming_dist_nany(&alpha, &beta), 1);
+    }
+
+    #[test]
+    fn test_single_bases() {
+        // Selfs
+        assert_eq!(
+            hamming_dist_nany(&BaseBits::new(b"A").unwrap(), &BaseBits::new(b"A").unwrap()),
+            0
+        );
+        assert_eq!(
+            hamming_dist_nany(&BaseBits::new(b"G").unwrap(), &BaseBits::new(b"G").unwrap()),
+            0
+        );
         assert_eq!(
             hamming_dist_nany(&BaseBits::new(b"C").unwrap(), &BaseBits::new(b"C").unwrap()),
             0
@@ -443,6 +2034,7 @@ mod tests {
         //&BaseBits::new("GATACAGATACAACNATAGCATT")), 4);
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn test_bb_to_string() {
         let alpha = BaseBits::new(b"GCTAN").unwrap();
@@ -454,6 +2046,1389 @@ mod tests {
         assert_eq!(long.to_string(), "GATACAGATACAACNATAGCA".to_string());
     }
 
+    #[test]
+    fn test_dist_positional_n() {
+        let alpha = BaseBits::new(b"ACNTGN").unwrap();
+        let beta = BaseBits::new(b"ACTTGA").unwrap();
+        assert_eq!(dist_positional_n(&alpha, &beta, 0.5, 2.0), 2.5);
+        assert_eq!(dist_positional_n(&alpha, &beta, 0.0, 1.0), 1.0);
+
+        // Ns in either sequence count once per position, substitutions count as 1
+        let alpha = BaseBits::new(b"NNCTGA").unwrap();
+        let beta = BaseBits::new(b"ANCTGT").unwrap();
+        assert_eq!(dist_positional_n(&alpha, &beta, 0.25, 2.0), 1.5);
+    }
+
+    #[test]
+    fn test_gray_ordered_ball_1() {
+        use std::collections::HashSet;
+        let seq = b"ACTNG";
+        let bb = BaseBits::new(seq).unwrap();
+        let ordered: Vec<BaseBits> = bb.gray_ordered_ball_1().collect();
+
+        let mut ball = HashSet::new();
+        for i in 0..seq.len() {
+            if seq[i] == b'N' {
+                continue;
+            }
+            for &base in b"ACTG" {
+                if base != seq[i] {
+                    let mut neighbor = seq.to_vec();
+                    neighbor[i] = base;
+                    ball.insert(BaseBits::new(&neighbor).unwrap());
+                }
+            }
+        }
+        assert_eq!(ordered.len(), ball.len());
+        assert_eq!(ordered.iter().cloned().collect::<HashSet<_>>(), ball);
+
+        for (i, pair) in ordered.windows(2).enumerate() {
+            let dist = hamming_dist_nany(&pair[0], &pair[1]);
+            if i % 3 == 2 {
+                // Moving on to the next position
+                assert_eq!(dist, 2);
+            } else {
+                assert_eq!(dist, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cross_dist_nany() {
+        let queries = vec![
+            BaseBits::new(b"ACTG").unwrap(),
+            BaseBits::new(b"GGGG").unwrap(),
+        ];
+        let refs = vec![
+            BaseBits::new(b"ACTG").unwrap(),
+            BaseBits::new(b"ACTT").unwrap(),
+            BaseBits::new(b"NGGG").unwrap(),
+        ];
+        assert_eq!(
+            cross_dist_nany(&queries, &refs),
+            vec![vec![0, 1, 2], vec![3, 4, 0]]
+        );
+        assert!(cross_dist_nany(&[], &refs).is_empty());
+    }
+
+    #[test]
+    fn test_tie_break() {
+        // Both refs are one mismatch away from the query
+        let query = BaseBits::new(b"ACTG").unwrap();
+        let refs: Vec<BaseBits> = [&b"TTTT"[..], b"ACTT", b"ACCG", b"GGGG"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(
+            nearest_nany_with(&query, &refs, TieBreak::LowestIndex),
+            Some((1, 1))
+        );
+        assert_eq!(
+            nearest_nany_with(&query, &refs, TieBreak::HighestIndex),
+            Some((2, 1))
+        );
+        assert_eq!(nearest_nany(&query, &refs), Some((1, 1)));
+        let (i, dist) = nearest_nany_with(&query, &refs, TieBreak::Random(3)).unwrap();
+        assert!(i == 1 || i == 2);
+        assert_eq!(dist, 1);
+
+        // An even split at the last position, and an N that only ties
+        let seqs: Vec<BaseBits> = [&b"ACTA"[..], b"ACNG", b"ACNA", b"ACTG"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(consensus(&seqs).decode(), b"ACTA".to_vec());
+        assert_eq!(
+            consensus_with(&seqs, TieBreak::HighestIndex).decode(),
+            b"ACTG".to_vec()
+        );
+        assert!(consensus(&[]).is_empty());
+
+        // Two members, equally far from each other
+        let pair = &refs[1..3];
+        assert_eq!(medoid_nany_with(pair, TieBreak::LowestIndex), Some(0));
+        assert_eq!(medoid_nany_with(pair, TieBreak::HighestIndex), Some(1));
+    }
+
+    #[test]
+    fn test_find_exact() {
+        let refs: Vec<BaseBits> = [&b"ACTG"[..], b"ACNG", b"TTTT", b"ACNG"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(find_exact(&BaseBits::new(b"ACNG").unwrap(), &refs), Some(1));
+        assert_eq!(find_exact(&BaseBits::new(b"TTTT").unwrap(), &refs), Some(2));
+        // Within n-any distance 0 of ACNG, but not equal to it
+        assert_eq!(find_exact(&BaseBits::new(b"ACCG").unwrap(), &refs), None);
+        assert_eq!(find_exact(&refs[0], &[]), None);
+    }
+
+    #[test]
+    fn test_assign_all_nany() {
+        let refs = vec![
+            BaseBits::new(b"AAAA").unwrap(),
+            BaseBits::new(b"CCCC").unwrap(),
+            BaseBits::new(b"GGGG").unwrap(),
+        ];
+        let queries = vec![
+            BaseBits::new(b"GGTG").unwrap(),
+            BaseBits::new(b"AAAA").unwrap(),
+            BaseBits::new(b"CCNA").unwrap(),
+        ];
+        assert_eq!(
+            assign_all_nany(&queries, &refs),
+            vec![(2, 1), (0, 0), (1, 1)]
+        );
+        assert_eq!(nearest_nany(&queries[0], &refs), Some((2, 1)));
+        assert_eq!(nearest_nany(&queries[0], &[]), None);
+        assert!(assign_all_nany(&queries, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_pairwise_matrix() {
+        let seqs: Vec<BaseBits> = (0..40u64)
+            .map(|i| {
+                let seq: Vec<u8> = (0..8)
+                    .map(|j| b"ACTGN"[(((i * 7) >> j) % 5) as usize])
+                    .collect();
+                BaseBits::new(&seq).unwrap()
+            })
+            .collect();
+        let mut naive = vec![vec![0; seqs.len()]; seqs.len()];
+        for (i, alpha) in seqs.iter().enumerate() {
+            for (j, beta) in seqs.iter().enumerate() {
+                naive[i][j] = hamming_dist_nany(alpha, beta);
+            }
+        }
+        assert_eq!(pairwise_matrix(&seqs), naive);
+        assert!(pairwise_matrix(&[]).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_assign_all_nany() {
+        let refs: Vec<BaseBits> = [&b"ACTGAC"[..], b"GGTTCA", b"TTTAAA", b"CAGNCA"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let queries: Vec<BaseBits> = (0..500u64)
+            .map(|i| {
+                let seq: Vec<u8> = (0..6).map(|j| b"ACTGN"[((i >> j) % 5) as usize]).collect();
+                BaseBits::new(&seq).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            par_assign_all_nany(&queries, &refs),
+            assign_all_nany(&queries, &refs)
+        );
+    }
+
+    #[test]
+    fn test_encoded_str_dist_nany() {
+        assert_eq!(encoded_str_dist_nany("ACTN", "ACTG"), Ok(0));
+        assert_eq!(hamming_str("ACTN", "ACTG"), 1);
+        assert_eq!(encoded_str_dist_nany("ACTA", "ACTG"), Ok(1));
+        assert!(encoded_str_dist_nany("ACTGACTGACTGACTGACTGAC", "ACTG").is_err());
+    }
+
+    #[test]
+    fn test_base_bits_capped() {
+        let seq = b"ACTGACTGACTGACTGA";
+        assert_eq!(
+            BaseBitsCapped::<16>::new(seq),
+            Err(BaseBitsError::TooLong { len: 17, max: 16 })
+        );
+        assert!(BaseBits::new(seq).is_ok());
+
+        let capped = BaseBitsCapped::<16>::new(&seq[..16]).unwrap();
+        let bb = BaseBits::new(&seq[..16]).unwrap();
+        assert_eq!(hamming_dist_nany(&capped, &bb), 0);
+        #[cfg(feature = "std")]
+        assert_eq!(capped.to_string(), bb.to_string());
+        assert_eq!(BaseBits::from(capped), bb);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_hamming_dist_deprecated() {
+        let alpha = BaseBits::new(b"ACTGN").unwrap();
+        let beta = BaseBits::new(b"TCTGA").unwrap();
+        assert_eq!(
+            hamming_dist(&alpha, &beta),
+            hamming_dist_nany(&alpha, &beta)
+        );
+        assert_eq!(hamming_dist(&alpha, &beta), 1);
+    }
+
+    #[test]
+    fn test_bench_call_sites() {
+        // Mirrors the calls made in benches/bench_basebits.rs so signature drift shows up in
+        // `cargo test` and not just when someone runs the benches.
+        assert_eq!(hamming_str("ACTGACTGACTG", "ACTGGGGGACTG"), 3);
+        let a = BaseBits::new(b"ACTGACTGACTG").unwrap();
+        let be = BaseBits::new(b"ACTGGGGGACTG").unwrap();
+        assert_eq!(hamming_dist_nany(&a, &be), 3);
+        assert_eq!(hamming_dist_none(&a, &be), 3);
+        assert_eq!(a.reverse_complement().reverse_complement(), a);
+        assert_eq!(assign_all_nany(&[a], &[a, be]), vec![(0, 0)]);
+        assert_eq!(hamming_dist_nany_batch(&a, &[a, be]), vec![0, 3]);
+        let mut out = vec![0; 2];
+        hamming_dist_nany_batch_into(&a, &[a, be], &mut out);
+        assert_eq!(out, vec![0, 3]);
+        assert_eq!(kmers(b"ACTGACTGACTGACTGACTGACTG", 21).count(), 4);
+    }
+
+    #[test]
+    fn test_first_n() {
+        assert_eq!(BaseBits::new(b"ACTNG").unwrap().first_n(), Some(3));
+        assert_eq!(BaseBits::new(b"ACTG").unwrap().first_n(), None);
+        assert_eq!(BaseBits::new(b"NCTNG").unwrap().first_n(), Some(0));
+        assert_eq!(BaseBits::new(b"").unwrap().first_n(), None);
+        let long = BaseBits::new(b"ACTGACTGACTGACTGACTGN").unwrap();
+        assert_eq!(long.first_n(), Some(20));
+    }
+
+    #[test]
+    fn test_trim_n() {
+        let trailing = BaseBits::new(b"ACTGNN").unwrap();
+        assert_eq!(trailing.trim_trailing_n(), BaseBits::new(b"ACTG").unwrap());
+        assert_eq!(trailing.trim_leading_n(), trailing);
+
+        let leading = BaseBits::new(b"NNACTG").unwrap();
+        assert_eq!(leading.trim_leading_n(), BaseBits::new(b"ACTG").unwrap());
+        assert_eq!(leading.trim_trailing_n(), leading);
+
+        let internal = BaseBits::new(b"ACNTG").unwrap();
+        assert_eq!(internal.trim_leading_n(), internal);
+        assert_eq!(internal.trim_trailing_n(), internal);
+
+        let all_n = BaseBits::new(b"NNNNNNNNNNNNNNNNNNNNN").unwrap();
+        assert_eq!(all_n.trim_trailing_n(), BaseBits::new(b"").unwrap());
+        assert_eq!(all_n.trim_leading_n(), BaseBits::new(b"").unwrap());
+    }
+
+    #[test]
+    fn test_suggest_correction_threshold() {
+        let refs = vec![
+            BaseBits::new(b"AAAAAAAA").unwrap(),
+            BaseBits::new(b"TTTTTTTT").unwrap(),
+        ];
+        let queries: Vec<BaseBits> = [
+            &b"AAAAAAAA"[..],
+            b"AAAAAAAA",
+            b"TTTTTTTT",
+            b"CAAAAAAA",
+            b"TTTTTTTC",
+            b"AAAGAAAA",
+            b"CCCCCTTT",
+            b"GGGGGAAA",
+            b"CCCCCCAA",
+            b"GGGGGGTT",
+        ]
+        .iter()
+        .map(|s| BaseBits::new(s).unwrap())
+        .collect();
+        assert_eq!(suggest_correction_threshold(&queries, &refs), 1);
+        assert_eq!(suggest_correction_threshold(&[], &refs), 0);
+
+        let zero = BaseBits::new(&[b'A'; MAX_BASES]).unwrap();
+        let mut wide = zero;
+        wide.code = !zero.code;
+        assert!(hamming_dist_nany(&wide, &zero) > MAX_BASES as u32);
+        assert_eq!(
+            suggest_correction_threshold(&[zero, zero, wide], &[zero]),
+            0
+        );
+    }
+
+    #[test]
+    fn test_medoid_nany() {
+        let cluster: Vec<BaseBits> = [&b"ACTGAC"[..], b"ACTGTT", b"ACTGAT", b"TCTGAT", b"ACAGAT"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(medoid_nany(&cluster), Some(2));
+        assert_eq!(medoid_nany(&cluster[..1]), Some(0));
+        assert_eq!(medoid_nany(&[]), None);
+    }
+
+    #[test]
+    fn test_contains_exact() {
+        let longer = BaseBits::new(b"GGACTGG").unwrap();
+        assert_eq!(
+            longer.contains_exact(&BaseBits::new(b"ACTG").unwrap()),
+            Some(2)
+        );
+        assert_eq!(
+            longer.contains_exact(&BaseBits::new(b"GG").unwrap()),
+            Some(0)
+        );
+        assert_eq!(
+            longer.contains_exact(&BaseBits::new(b"TGG").unwrap()),
+            Some(4)
+        );
+        assert_eq!(
+            longer.contains_exact(&BaseBits::new(b"ANTG").unwrap()),
+            Some(2)
+        );
+        assert_eq!(
+            longer.contains_exact(&BaseBits::new(b"ACCG").unwrap()),
+            None
+        );
+        assert_eq!(
+            longer.contains_exact(&BaseBits::new(b"GGACTGGA").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_blocked_self_dist_nany() {
+        use std::collections::HashMap;
+        let seqs: Vec<BaseBits> = (0..11u64)
+            .map(|i| {
+                let seq: Vec<u8> = (0..5)
+                    .map(|j| b"ACTGN"[((i * 7 + j) % 5) as usize])
+                    .collect();
+                BaseBits::new(&seq).unwrap()
+            })
+            .collect();
+        for &block in &[0, 1, 3, 4, 11, 20] {
+            let mut seen = HashMap::new();
+            blocked_self_dist_nany(&seqs, block, |i, j, dist| {
+                assert!(i < j);
+                assert!(seen.insert((i, j), dist).is_none());
+            });
+            assert_eq!(seen.len(), 11 * 10 / 2);
+            for ((i, j), dist) in seen {
+                assert_eq!(dist, hamming_dist_nany(&seqs[i], &seqs[j]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_group_runs() {
+        let stream: Vec<BaseBits> = [&b"AAAA"[..], b"AAAA", b"AAAA", b"ACTG", b"NCTG", b"NCTG"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let runs: Vec<(BaseBits, u64)> = group_runs(stream.into_iter()).collect();
+        assert_eq!(
+            runs,
+            vec![
+                (BaseBits::new(b"AAAA").unwrap(), 3),
+                (BaseBits::new(b"ACTG").unwrap(), 1),
+                (BaseBits::new(b"NCTG").unwrap(), 2),
+            ]
+        );
+        assert_eq!(group_runs(Vec::new().into_iter()).count(), 0);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let bb = BaseBits::new(b"ACTG").unwrap();
+        assert_eq!(bb.reverse(), BaseBits::new(b"GTCA").unwrap());
+        assert_eq!(bb.reverse().reverse(), bb);
+
+        let with_n = BaseBits::new(b"NACTGGATTACAN").unwrap();
+        assert_eq!(with_n.reverse(), BaseBits::new(b"NACATTAGGTCAN").unwrap());
+        assert_eq!(with_n.reverse().reverse(), with_n);
+
+        let empty = BaseBits::new(b"").unwrap();
+        assert_eq!(empty.reverse(), empty);
+    }
+
+    #[test]
+    fn test_windowed_dist_nany() {
+        let alpha = BaseBits::new(b"ACTGACTG").unwrap();
+        let beta = BaseBits::new(b"TCAGTNTA").unwrap();
+        assert_eq!(windowed_dist_nany(&alpha, &beta, 2, 3), 2);
+        assert_eq!(
+            windowed_dist_nany(&alpha, &beta, 2, 3),
+            hamming_dist_nany(
+                &BaseBits::new(b"TGA").unwrap(),
+                &BaseBits::new(b"AGT").unwrap()
+            )
+        );
+        assert_eq!(
+            windowed_dist_nany(&alpha, &beta, 0, 8),
+            hamming_dist_nany(&alpha, &beta)
+        );
+        assert_eq!(windowed_dist_nany(&alpha, &beta, 5, 0), 0);
+    }
+
+    #[test]
+    fn test_reverse_complement() {
+        let bb = BaseBits::new(b"AACTGG").unwrap();
+        assert_eq!(bb.reverse_complement(), BaseBits::new(b"CCAGTT").unwrap());
+        assert_eq!(bb.reverse_complement().reverse_complement(), bb);
+    }
+
+    #[test]
+    fn test_reverse_complement_with_n() {
+        let bb = BaseBits::new(b"NACNGTTAN").unwrap();
+        let rc = bb.reverse_complement();
+        assert_eq!(rc, BaseBits::new(b"NTAACNGTN").unwrap());
+        assert!(rc.is_valid());
+        assert_eq!(rc.first_n(), Some(0));
+        assert_eq!(rc.reverse_complement(), bb);
+
+        // The N mask has to move with the bases for n-any to line up
+        let other = BaseBits::new(b"ATAACAGTA").unwrap();
+        assert_eq!(hamming_dist_nany(&rc, &other), 0);
+        assert_eq!(hamming_dist_none(&rc, &other), 3);
+
+        let long = BaseBits::new(b"GATNACAGATACNACNATAGC").unwrap();
+        #[cfg(feature = "std")]
+        assert_eq!(
+            long.reverse_complement().to_string(),
+            "GCTATNGTNGTATCTGTNATC"
+        );
+        assert_eq!(long.reverse_complement().reverse_complement(), long);
+    }
+
+    #[test]
+    fn test_canonical() {
+        let bb = BaseBits::new(b"ACTG").unwrap();
+        let rc = bb.reverse_complement();
+        assert_eq!(bb.canonical(), rc.canonical());
+        assert!(bb.canonical() == bb || bb.canonical() == rc);
+        let palindrome = BaseBits::new(b"GAATTC").unwrap();
+        assert_eq!(palindrome.canonical(), palindrome);
+    }
+
+    #[test]
+    fn test_is_palindrome() {
+        assert!(BaseBits::new(b"GAATTC").unwrap().is_palindrome());
+        assert!(!BaseBits::new(b"ACTG").unwrap().is_palindrome());
+        assert!(!BaseBits::new(b"GANTTC").unwrap().is_palindrome());
+    }
+
+    #[test]
+    fn test_gc_fraction() {
+        assert_eq!(BaseBits::new(b"GCGC").unwrap().gc_fraction(), 1.0);
+        assert_eq!(BaseBits::new(b"ATAT").unwrap().gc_fraction(), 0.0);
+        assert_eq!(BaseBits::new(b"ACTG").unwrap().gc_fraction(), 0.5);
+        // N's count toward neither side
+        let bb = BaseBits::new(b"GNNA").unwrap();
+        assert_eq!(bb.gc_count(), 1);
+        assert_eq!(bb.gc_fraction(), 0.5);
+        assert_eq!(BaseBits::new(b"NNN").unwrap().gc_fraction(), 0.0);
+
+        let full = BaseBits::new(&[b'G'; MAX_BASES]).unwrap();
+        assert_eq!(full.gc_count(), MAX_BASES as u32);
+    }
+
+    #[test]
+    fn test_is_low_complexity() {
+        let homopolymer = BaseBits::new(b"AAAAA").unwrap();
+        assert!(homopolymer.is_low_complexity(4, 2));
+        assert!(!homopolymer.is_low_complexity(5, 2));
+
+        let dinuc = BaseBits::new(b"ACACAC").unwrap();
+        assert!(dinuc.is_low_complexity(4, 2));
+        assert!(!dinuc.is_low_complexity(4, 3));
+
+        let clean = BaseBits::new(b"ACTGCA").unwrap();
+        assert!(!clean.is_low_complexity(4, 2));
+        assert!(!clean.is_low_complexity(1, 1));
+
+        // N's break runs
+        assert!(!BaseBits::new(b"AAANAA").unwrap().is_low_complexity(3, 2));
+        assert!(!BaseBits::new(b"ACANCAC").unwrap().is_low_complexity(4, 2));
+        assert!(BaseBits::new(b"TACACACT").unwrap().is_low_complexity(4, 2));
+    }
+
+    #[test]
+    fn test_profile() {
+        let seqs: Vec<BaseBits> = [&b"ACTG"[..], b"ACTA", b"NCGA"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(
+            profile(&seqs),
+            vec![
+                [2, 0, 0, 0, 1],
+                [0, 3, 0, 0, 0],
+                [0, 0, 2, 1, 0],
+                [2, 0, 0, 1, 0]
+            ]
+        );
+        assert!(profile(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_from_profile() {
+        let counts = [
+            [9, 1, 0, 0, 0],
+            [0, 5, 2, 2, 1],
+            [1, 1, 7, 1, 0],
+            [0, 0, 3, 4, 3],
+        ];
+        assert_eq!(
+            BaseBits::from_profile(&counts).unwrap(),
+            BaseBits::new(b"ACTG").unwrap()
+        );
+        // Ties go to the first base in profile order and N's only win outright
+        let counts = [[2, 2, 0, 0, 0], [0, 0, 1, 1, 1], [0, 0, 0, 1, 2]];
+        assert_eq!(
+            BaseBits::from_profile(&counts).unwrap(),
+            BaseBits::new(b"ATN").unwrap()
+        );
+        assert!(BaseBits::from_profile(&[[1, 0, 0, 0, 0]; MAX_BASES + 1]).is_err());
+    }
+
+    #[test]
+    fn test_could_be_same() {
+        // nany distance 1, none distance 2
+        let alpha = BaseBits::new(b"ACTGN").unwrap();
+        let beta = BaseBits::new(b"ACTTA").unwrap();
+        assert!(could_be_same(&alpha, &beta, 1, true));
+        assert!(!could_be_same(&alpha, &beta, 0, true));
+        assert!(could_be_same(&alpha, &beta, 2, false));
+        assert!(!could_be_same(&alpha, &beta, 1, false));
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(
+            BaseBits::new(b"ATGTAA").unwrap().translate(0),
+            b"M*".to_vec()
+        );
+        assert_eq!(
+            BaseBits::new(b"CATGGCNTAGG").unwrap().translate(1),
+            b"MX*".to_vec()
+        );
+        assert_eq!(
+            BaseBits::new(b"ATGTAA").unwrap().translate(2),
+            b"V".to_vec()
+        );
+        assert!(BaseBits::new(b"AT").unwrap().translate(0).is_empty());
+        assert!(BaseBits::new(b"AT").unwrap().translate(5).is_empty());
+    }
+
+    #[test]
+    fn test_distances_nany() {
+        let query = BaseBits::new(b"ACTG").unwrap();
+        let refs: Vec<BaseBits> = [&b"ACTG"[..], b"ACTT", b"GGGG", b"NCTA"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let lazy: Vec<u32> = distances_nany(&query, refs.iter()).collect();
+        let manual: Vec<u32> = refs.iter().map(|r| hamming_dist_nany(&query, r)).collect();
+        assert_eq!(lazy, manual);
+        assert_eq!(
+            distances_nany(&query, refs.iter())
+                .filter(|&d| d <= 1)
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_correctable_errors_nany() {
+        let seqs: Vec<BaseBits> = [&b"AAAAAA"[..], b"CCCAAA", b"AAACCC", b"GGGGGG"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(correctable_errors_nany(&seqs), Some(1));
+        assert_eq!(correctable_errors_nany(&[seqs[0], seqs[3]]), Some(2));
+        assert_eq!(correctable_errors_nany(&[seqs[0], seqs[0]]), Some(0));
+        assert_eq!(correctable_errors_nany(&seqs[..1]), None);
+    }
+
+    #[test]
+    fn test_new_reporting() {
+        let (bb, coerced) = BaseBits::new_reporting(b"ACT9X").unwrap();
+        assert_eq!(bb, BaseBits::new(b"ACTNN").unwrap());
+        assert_eq!(coerced, 2);
+        assert_eq!(BaseBits::new_reporting(b"ACTGN").unwrap().1, 0);
+        assert!(BaseBits::new_reporting(&[b'A'; MAX_BASES + 1]).is_err());
+    }
+
+    #[test]
+    fn test_dist_nany_saturating() {
+        let alpha = BaseBits::new(b"ACTGAC").unwrap();
+        let far = BaseBits::new(b"CAGCAC").unwrap();
+        let near = BaseBits::new(b"ACTGAA").unwrap();
+        assert_eq!(hamming_dist_nany(&alpha, &far), 4);
+        assert_eq!(dist_nany_saturating(&alpha, &far, 2), (2, true));
+        assert_eq!(dist_nany_saturating(&alpha, &near, 2), (1, false));
+        assert_eq!(dist_nany_saturating(&alpha, &far, 4), (4, false));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let alpha = BaseBits::new(b"ACTG").unwrap();
+        let beta = BaseBits::new(b"ACAG").unwrap();
+        assert!(alpha.approx_eq(&beta, 1));
+        assert!(!alpha.approx_eq(&beta, 0));
+        assert!(alpha.approx_eq(&alpha, 0));
+        assert!(!alpha.approx_eq(&BaseBits::new(b"ACTN").unwrap(), 0));
+    }
+
+    #[test]
+    fn test_mean_dist_nany() {
+        let query = BaseBits::new(b"ACTG").unwrap();
+        let refs: Vec<BaseBits> = [&b"ACTG"[..], b"ACTT", b"GGGG", b"NCTA"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(mean_dist_nany(&query, &refs), 1.25);
+        assert!(mean_dist_nany(&query, &[]).is_nan());
+    }
+
+    #[test]
+    fn test_hamming_weight() {
+        assert_eq!(BaseBits::new(b"AAAA").unwrap().hamming_weight(), 0);
+        assert_eq!(BaseBits::new(b"CCCC").unwrap().hamming_weight(), 4);
+        assert_eq!(BaseBits::new(b"ACTGN").unwrap().hamming_weight(), 3);
+        let bb = BaseBits::new(b"GATNACA").unwrap();
+        assert_eq!(
+            bb.hamming_weight(),
+            hamming_dist_nany(&bb, &BaseBits::new(b"AAAAAAA").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_agreement_mask() {
+        let query = BaseBits::new(b"ACTG").unwrap();
+        let consensus = BaseBits::new(b"AGTG").unwrap();
+        assert_eq!(agreement_mask(&query, &consensus), 0b1101);
+        assert_eq!(agreement_mask(&query, &consensus).count_ones(), 3);
+        let with_n = BaseBits::new(b"NCTA").unwrap();
+        assert_eq!(agreement_mask(&with_n, &query), 0b0111);
+    }
+
+    #[test]
+    fn test_retain_n_free() {
+        let mut seqs: Vec<BaseBits> = [&b"ACTG"[..], b"ACNG", b"GGGG", b"NNNN", b"TTTA", b"TTTN"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        retain_n_free(&mut seqs);
+        let expected: Vec<BaseBits> = [&b"ACTG"[..], b"GGGG", b"TTTA"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(seqs, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_write_logo_tsv() {
+        let seqs: Vec<BaseBits> = [&b"ACTG"[..], b"ACTA", b"NCGA", b"TCGA"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let mut out = Vec::new();
+        write_logo_tsv(&seqs, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "pos\tA\tC\tT\tG\tN");
+        assert_eq!(lines[1], "0\t0.5000\t0.0000\t0.2500\t0.0000\t0.2500");
+        assert_eq!(lines[3], "2\t0.0000\t0.0000\t0.5000\t0.5000\t0.0000");
+    }
+
+    #[test]
+    fn test_dist_nany_raw() {
+        let alpha = BaseBits::new(b"ACTGNA").unwrap();
+        let beta = BaseBits::new(b"TCTGAC").unwrap();
+        assert_eq!(
+            dist_nany_raw((alpha.code, alpha.nbits), (beta.code, beta.nbits)),
+            hamming_dist_nany(&alpha, &beta)
+        );
+        assert_eq!(
+            dist_nany_raw((alpha.code, alpha.nbits), (beta.code, beta.nbits)),
+            2
+        );
+    }
+
+    #[test]
+    fn test_new_clamped() {
+        assert_eq!(
+            BaseBits::new_clamped(b"ACT", 6),
+            BaseBits::new(b"ACTNNN").unwrap()
+        );
+        assert_eq!(
+            BaseBits::new_clamped(b"ACTGACTG", 5),
+            BaseBits::new(b"ACTGA").unwrap()
+        );
+        assert_eq!(
+            BaseBits::new_clamped(b"ACTG", 4),
+            BaseBits::new(b"ACTG").unwrap()
+        );
+        assert_eq!(BaseBits::new_clamped(b"", 50).len(), MAX_BASES);
+    }
+
+    #[test]
+    fn test_best_phase_dist_nany() {
+        let alpha = BaseBits::new(b"ACTGACGT").unwrap();
+        let ahead = BaseBits::new(b"CTGACGTA").unwrap();
+        let behind = BaseBits::new(b"GACTGACG").unwrap();
+        assert_eq!(hamming_dist_nany(&alpha, &ahead), 8);
+        assert_eq!(best_phase_dist_nany(&alpha, &ahead, 1), (1, 0));
+        assert_eq!(best_phase_dist_nany(&alpha, &behind, 2), (-1, 0));
+        assert_eq!(best_phase_dist_nany(&alpha, &behind, 0).0, 0);
+        assert_eq!(best_phase_dist_nany(&alpha, &alpha, 3), (0, 0));
+    }
+
+    #[test]
+    fn test_normalized() {
+        let bb = BaseBits::new(b"ACTGN").unwrap();
+        let other = BaseBits::new(b"ACTTA").unwrap();
+        assert_eq!(bb.normalized(), bb);
+
+        let dirty = BaseBits {
+            code: bb.code | (0b1011 << 60),
+            nbits: bb.nbits & !(0b111 << 30),
+            len: bb.len,
+        };
+        assert_ne!(
+            hamming_dist_none(&dirty, &other),
+            hamming_dist_none(&bb, &other)
+        );
+        let clean = dirty.normalized();
+        assert_eq!(clean, bb);
+        assert_eq!(clean.decode(), b"ACTGN".to_vec());
+        assert_eq!(
+            hamming_dist_nany(&clean, &other),
+            hamming_dist_nany(&bb, &other)
+        );
+        assert_eq!(
+            hamming_dist_none(&clean, &other),
+            hamming_dist_none(&bb, &other)
+        );
+    }
+
+    #[test]
+    fn test_is_member() {
+        let rep = BaseBits::new(b"ACTGACTG").unwrap();
+        let candidate = BaseBits::new(b"ACTTACNG").unwrap();
+        assert_eq!(hamming_dist_none(&candidate, &rep), 2);
+        assert!(is_member(&candidate, &rep, 2));
+        assert!(is_member(&candidate, &rep, 3));
+        assert!(!is_member(&candidate, &rep, 1));
+    }
+
+    #[test]
+    fn test_rc_self_distance() {
+        assert_eq!(BaseBits::new(b"GAATTC").unwrap().rc_self_distance(), 0);
+        // AACTGG vs CCAGTT
+        assert_eq!(BaseBits::new(b"AACTGG").unwrap().rc_self_distance(), 6);
+        // GAGTTC vs GAACTC
+        assert_eq!(BaseBits::new(b"GAGTTC").unwrap().rc_self_distance(), 2);
+        // N's never match under n-none, GANTTC vs GAANTC
+        assert_eq!(BaseBits::new(b"GANTTC").unwrap().rc_self_distance(), 2);
+    }
+
+    #[test]
+    fn test_resolve_against() {
+        let reference = BaseBits::new(b"ACTG").unwrap();
+        assert_eq!(
+            BaseBits::new(b"ACTN").unwrap().resolve_against(&reference),
+            reference
+        );
+        assert_eq!(
+            BaseBits::new(b"NGTA").unwrap().resolve_against(&reference),
+            BaseBits::new(b"AGTA").unwrap()
+        );
+        assert_eq!(
+            BaseBits::new(b"NCNA")
+                .unwrap()
+                .resolve_against(&BaseBits::new(b"GCNG").unwrap()),
+            BaseBits::new(b"GCNA").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_consecutive_dists_nany() {
+        let stream: Vec<BaseBits> = [&b"AAAA"[..], b"AAAT", b"AAAT", b"GGNT", b"CCCC"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let dists: Vec<u32> = consecutive_dists_nany(stream.into_iter()).collect();
+        assert_eq!(dists, vec![1, 0, 2, 3]);
+        assert_eq!(
+            consecutive_dists_nany(vec![BaseBits::new(b"A").unwrap()].into_iter()).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let bb = BaseBits::new(b"ACTGN").unwrap();
+        assert!(bb.is_valid());
+        assert!(BaseBits::new(b"").unwrap().is_valid());
+        assert!(BaseBits::new(&[b'N'; MAX_BASES]).unwrap().is_valid());
+
+        // Too long
+        assert!(!BaseBits {
+            len: MAX_BASES + 1,
+            ..bb
+        }
+        .is_valid());
+        // Stray code bits above len
+        assert!(!BaseBits {
+            code: bb.code | 1 << 40,
+            ..bb
+        }
+        .is_valid());
+        // Cleared nbits above len
+        assert!(!BaseBits {
+            nbits: bb.nbits & !(1 << 40),
+            ..bb
+        }
+        .is_valid());
+        // Partially set nbits group
+        assert!(!BaseBits {
+            nbits: bb.nbits & !(0b10 << 3),
+            ..bb
+        }
+        .is_valid());
+        // N flagged position holding a called base
+        assert!(!BaseBits {
+            nbits: bb.nbits & !(0b111 << 3),
+            ..bb
+        }
+        .is_valid());
+        // Called position holding the N code
+        assert!(!BaseBits {
+            nbits: bb.nbits | 0b111,
+            ..bb
+        }
+        .is_valid());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_dist_nany_batch_to_writer() {
+        let query = BaseBits::new(b"ACTG").unwrap();
+        let refs: Vec<BaseBits> = [&b"ACTG"[..], b"ACTT", b"GGGG", b"NCTA"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let mut out = Vec::new();
+        dist_nany_batch_to_writer(&query, &refs, &mut out).unwrap();
+        assert_eq!(out.len(), refs.len() * 4);
+        let dists: Vec<u32> = out
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(dists, vec![0, 1, 3, 1]);
+    }
+
+    #[test]
+    fn test_knn_graph_nany() {
+        let seqs: Vec<BaseBits> = [&b"AAAA"[..], b"AAAT", b"AATT", b"CCCC"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        assert_eq!(
+            knn_graph_nany(&seqs, 2),
+            vec![
+                vec![(1, 1), (2, 2)],
+                vec![(0, 1), (2, 1)],
+                vec![(1, 1), (0, 2)],
+                vec![(0, 4), (1, 4)],
+            ]
+        );
+        assert_eq!(knn_graph_nany(&seqs, 10)[0].len(), 3);
+        assert!(knn_graph_nany(&seqs, 0).iter().all(|n| n.is_empty()));
+    }
+
+    #[test]
+    fn test_new_iupac_collapsed() {
+        let reps = [(b'R', b'A'), (b'Y', b'C')];
+        assert_eq!(
+            BaseBits::new_iupac_collapsed(b"RCTG", &reps).unwrap(),
+            BaseBits::new(b"ACTG").unwrap()
+        );
+        assert_eq!(
+            BaseBits::new_iupac_collapsed(b"RYWG", &reps).unwrap(),
+            BaseBits::new(b"ACNG").unwrap()
+        );
+        assert!(BaseBits::new_iupac_collapsed(&[b'R'; MAX_BASES + 1], &reps).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_phred_weighted_dist() {
+        let alpha = BaseBits::new(b"ACTGN").unwrap();
+        let beta = BaseBits::new(b"TCTCA").unwrap();
+        let high = [40, 40, 40, 40, 40];
+        let low_first = [10, 40, 40, 40, 40];
+        let low_fourth = [40, 40, 40, 3, 40];
+
+        let both_high = phred_weighted_dist(&alpha, &high, &beta, &high);
+        assert!((both_high - 2.0 * (1.0 - 2e-4)).abs() < 1e-9);
+
+        let one_low = phred_weighted_dist(&alpha, &low_first, &beta, &high);
+        assert!((one_low - ((1.0 - 0.1 - 1e-4) + (1.0 - 2e-4))).abs() < 1e-9);
+        assert!(one_low < both_high);
+
+        // Two low quality calls can't be worth less than nothing
+        let floored = phred_weighted_dist(&alpha, &low_fourth, &beta, &low_fourth);
+        assert!((floored - (1.0 - 2e-4)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_len() {
+        let bb = BaseBits::new(b"ACTGNACTGA").unwrap();
+        assert_eq!(bb.len(), 10);
+        assert!(!bb.is_empty());
+        assert!(BaseBits::new(b"").unwrap().is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_write_fasta() {
+        let mut out = Vec::new();
+        BaseBits::new(b"ACTGN")
+            .unwrap()
+            .write_fasta("bc1", &mut out)
+            .unwrap();
+        BaseBits::new(b"GGA")
+            .unwrap()
+            .write_fasta("bc2 sample=x", &mut out)
+            .unwrap();
+        assert_eq!(out, b">bc1\nACTGN\n>bc2 sample=x\nGGA\n".to_vec());
+    }
+
+    #[test]
+    fn test_hamming_dist_within() {
+        let alpha = BaseBits::new(b"ACTGAC").unwrap();
+        let beta = BaseBits::new(b"ACAGNT").unwrap();
+        assert_eq!(hamming_dist_within(&alpha, &beta, 2), Some(2));
+        assert_eq!(hamming_dist_within(&alpha, &beta, 3), Some(2));
+        assert_eq!(hamming_dist_within(&alpha, &beta, 1), None);
+        assert_eq!(hamming_dist_within(&alpha, &alpha, 0), Some(0));
+    }
+
+    #[test]
+    fn test_signature() {
+        assert_eq!(BaseBits::new(b"GCNAT").unwrap().signature(), (2, 1));
+        assert_eq!(BaseBits::new(b"").unwrap().signature(), (0, 0));
+        let alpha = BaseBits::new(b"GGCCAA").unwrap().signature();
+        let beta = BaseBits::new(b"AATTNA").unwrap().signature();
+        assert_eq!(signature_lower_bound(alpha, beta), 3);
+        assert_eq!(signature_lower_bound(beta, alpha), 3);
+    }
+
+    #[test]
+    fn test_signature_lower_bound_holds() {
+        let mut state = 12345u64;
+        let mut random_seq = |len: usize| -> BaseBits {
+            let seq: Vec<u8> = (0..len)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    b"ACTGN"[((state >> 33) % 5) as usize]
+                })
+                .collect();
+            BaseBits::new(&seq).unwrap()
+        };
+        for i in 0..2000 {
+            let len = i % (MAX_BASES + 1);
+            let alpha = random_seq(len);
+            let beta = random_seq(len);
+            assert!(
+                signature_lower_bound(alpha.signature(), beta.signature())
+                    <= hamming_dist_nany(&alpha, &beta)
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_nucleic() {
+        let rna = BaseBits::new_nucleic(b"ACUG").unwrap();
+        let dna = BaseBits::new_nucleic(b"ACTG").unwrap();
+        assert_eq!(hamming_dist_nany(&rna, &dna), 0);
+        assert_eq!(rna, dna);
+        assert_eq!(dna, BaseBits::new(b"ACTG").unwrap());
+        assert_eq!(rna, BaseBits::new_rna(b"ACUG").unwrap());
+        assert_eq!(
+            BaseBits::new_nucleic(b"TUNX").unwrap().decode(),
+            b"TTNN".to_vec()
+        );
+        assert!(BaseBits::new_nucleic(&[b'U'; MAX_BASES + 1]).is_err());
+    }
+
+    #[test]
+    fn test_new_rna() {
+        let rna = BaseBits::new_rna(b"ACUG").unwrap();
+        let dna = BaseBits::new(b"ACTG").unwrap();
+        assert_eq!(rna.code, dna.code);
+        assert_eq!(rna, dna);
+        assert_eq!(hamming_dist_none(&rna, &dna), 0);
+        assert_eq!(rna.decode_rna(), b"ACUG".to_vec());
+        assert_eq!(rna.decode(), b"ACTG".to_vec());
+        assert_eq!(
+            BaseBits::new_rna(b"ACTG").unwrap(),
+            BaseBits::new(b"ACNG").unwrap()
+        );
+        assert!(BaseBits::new_rna(&[b'U'; MAX_BASES + 1]).is_err());
+    }
+
+    #[test]
+    fn test_try_from_and_from_str() {
+        let expected = BaseBits::new(b"ACTG").unwrap();
+        assert_eq!(BaseBits::try_from(&b"ACTG"[..]), Ok(expected));
+        let parsed: BaseBits = "ACTG".parse().unwrap();
+        assert_eq!(parsed, expected);
+        assert!("ACTGACTGACTGACTGACTGAC".parse::<BaseBits>().is_err());
+
+        // Multibyte characters become a single N each
+        let parsed: BaseBits = "AC\u{e9}G".parse().unwrap();
+        assert_eq!(parsed, BaseBits::new(b"ACNG").unwrap());
+        assert_eq!(parsed.len(), 4);
+    }
+
+    #[test]
+    fn test_window_pairwise_nany() {
+        let read = b"ACTGACTTACNG";
+        // ACTGACTT, ACTTACNG, GACTTACN
+        let windows = [(0, 8), (4, 8), (3, 8)];
+        assert_eq!(
+            window_pairwise_nany(read, &windows).unwrap(),
+            vec![vec![0, 2, 7], vec![2, 0, 5], vec![7, 5, 0]]
+        );
+        assert!(window_pairwise_nany(&[b'A'; 30], &[(0, 22)]).is_err());
+        assert!(window_pairwise_nany(read, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_n_count() {
+        assert_eq!(BaseBits::new(b"ANNNA").unwrap().n_count(), 3);
+        assert_eq!(BaseBits::new(b"ACTG").unwrap().n_count(), 0);
+        assert_eq!(BaseBits::new(b"").unwrap().n_count(), 0);
+        assert_eq!(BaseBits::new(&[b'N'; MAX_BASES]).unwrap().n_count(), 21);
+    }
+
+    #[test]
+    fn test_impute_ns() {
+        let panel: Vec<BaseBits> = [&b"ACTG"[..], b"AGTC", b"AGTN", b"TGCN"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let counts = profile(&panel);
+        // A dominates the first position, G the second
+        let read = BaseBits::new(b"NNTC").unwrap();
+        let imputed = read.impute_ns(&counts);
+        assert_eq!(imputed.decode(), b"AGTC".to_vec());
+        assert!(imputed.is_valid());
+
+        // The N's in the panel don't outvote the called bases, and C wins the tie with G
+        assert_eq!(
+            BaseBits::new(b"ACTN").unwrap().impute_ns(&counts).decode(),
+            b"ACTC".to_vec()
+        );
+        // Nothing to go on past the end of the profile or where it has no called bases
+        assert_eq!(
+            BaseBits::new(b"ACTGN").unwrap().impute_ns(&counts).decode(),
+            b"ACTGN".to_vec()
+        );
+        let unknown = [[0, 0, 0, 0, 3]];
+        assert_eq!(
+            BaseBits::new(b"N").unwrap().impute_ns(&unknown).decode(),
+            b"N".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_new_unchecked() {
+        for seq in &[&b"ACTGN"[..], b"", b"ACTGNACTGNACTGNACTGNA"] {
+            let bb = unsafe { BaseBits::new_unchecked(seq) };
+            assert_eq!(bb, BaseBits::new(seq).unwrap());
+        }
+        let too_long = unsafe { BaseBits::new_unchecked(&[b'A'; MAX_BASES + 1]) };
+        assert!(!too_long.is_valid());
+    }
+
+    #[test]
+    fn test_bases_macro() {
+        assert_eq!(bases!("ACTG"), BaseBits::new(b"ACTG").unwrap());
+        assert_eq!(bases!(""), BaseBits::new(b"").unwrap());
+        const FULL: BaseBits = bases!("ACTGNACTGNACTGNACTGNA");
+        assert_eq!(FULL, BaseBits::new(b"ACTGNACTGNACTGNACTGNA").unwrap());
+        assert_eq!(BaseBits::new_const(b"AXN"), BaseBits::new(b"AXN").unwrap());
+    }
+
+    #[test]
+    fn test_fuzzy_keys() {
+        let alpha = BaseBits::new(b"ACTGACTG").unwrap();
+        let keys = alpha.fuzzy_keys();
+        assert_eq!(keys.len(), alpha.len());
+        assert_eq!(alpha.without_base_at(3).decode(), b"ACTACTG".to_vec());
+        assert!(alpha.without_base_at(0).is_valid());
+        assert!(BaseBits::new(b"ACNGA")
+            .unwrap()
+            .without_base_at(4)
+            .is_valid());
+
+        for neighbor in alpha.gray_ordered_ball_1() {
+            let shared = neighbor
+                .fuzzy_keys()
+                .iter()
+                .filter(|key| keys.contains(key))
+                .count();
+            assert_eq!(shared, 1, "{:?}", neighbor);
+        }
+
+        let far = BaseBits::new(b"TTTGACAA").unwrap();
+        assert!(far.fuzzy_keys().iter().all(|key| !keys.contains(key)));
+        assert!(BaseBits::new(b"").unwrap().fuzzy_keys().is_empty());
+    }
+
+    #[test]
+    fn test_with_mask() {
+        let alpha = BaseBits::new(b"ACGG").unwrap();
+        let beta = BaseBits::new(b"ACTG").unwrap();
+        assert_eq!(hamming_dist_nany(&alpha, &beta), 1);
+        let masked = alpha.with_mask(&[2]);
+        assert_eq!(hamming_dist_nany(&masked, &beta), 0);
+        assert_eq!(masked, BaseBits::new(b"ACNG").unwrap());
+        assert!(masked.is_valid());
+        assert_eq!(alpha.with_mask(&[2, 2, 4]), masked);
+    }
+
+    #[test]
+    fn test_mask_cycles() {
+        let bb = BaseBits::new(b"ACTGACTG").unwrap();
+        let masked = bb.mask_cycles(&[0, 5]);
+        assert_eq!(masked.decode(), b"NCTGANTG".to_vec());
+        assert_eq!(masked.n_count(), 2);
+        assert!(masked.is_valid());
+        let other = BaseBits::new(b"TCTGAATG").unwrap();
+        assert_eq!(hamming_dist_nany(&bb, &other), 2);
+        assert_eq!(hamming_dist_nany(&masked, &other), 0);
+
+        assert_eq!(bb.mask_cycles(&[8, 100]), bb);
+        assert_eq!(bb.mask_cycles(&[]), bb);
+    }
+
+    #[test]
+    fn test_mismatch_positions() {
+        let alpha = BaseBits::new(b"ACTG").unwrap();
+        let beta = BaseBits::new(b"AGTG").unwrap();
+        assert_eq!(mismatch_positions(&alpha, &beta), vec![1]);
+        assert_eq!(mismatch_positions(&alpha, &alpha), Vec::<usize>::new());
+
+        let beta = BaseBits::new(b"TGNG").unwrap();
+        assert_eq!(mismatch_positions(&alpha, &beta), vec![0, 1]);
+        assert_eq!(
+            mismatch_positions(&alpha, &beta).len() as u32,
+            hamming_dist_nany(&alpha, &beta)
+        );
+    }
+
+    #[test]
+    fn test_transition_transversion_dist() {
+        // A -> G is a transition, T -> G a transversion
+        let alpha = BaseBits::new(b"ACTG").unwrap();
+        let beta = BaseBits::new(b"GCGG").unwrap();
+        assert_eq!(transition_dist(&alpha, &beta), 1);
+        assert_eq!(transversion_dist(&alpha, &beta), 1);
+
+        // Every pair of called bases
+        for &a in b"ACGT" {
+            for &b in b"ACGT" {
+                let (alpha, beta) = (BaseBits::new(&[a]).unwrap(), BaseBits::new(&[b]).unwrap());
+                let transition = matches!(
+                    (a, b),
+                    (b'A', b'G') | (b'G', b'A') | (b'C', b'T') | (b'T', b'C')
+                );
+                let expected = if a == b {
+                    (0, 0)
+                } else if transition {
+                    (1, 0)
+                } else {
+                    (0, 1)
+                };
+                assert_eq!(
+                    (
+                        transition_dist(&alpha, &beta),
+                        transversion_dist(&alpha, &beta)
+                    ),
+                    expected
+                );
+            }
+        }
+
+        let beta = BaseBits::new(b"NCNG").unwrap();
+        assert_eq!(transition_dist(&alpha, &beta), 0);
+        assert_eq!(transversion_dist(&alpha, &beta), 0);
+    }
+
+    #[test]
+    fn test_hamming_dist_nany_checked() {
+        let alpha = BaseBits::new(b"ACTGN").unwrap();
+        let beta = BaseBits::new(b"ACTTA").unwrap();
+        assert_eq!(hamming_dist_nany_checked(&alpha, &beta), Ok(1));
+
+        // Unchecked, the missing base reads as an A and matches
+        let short = BaseBits::new(b"CTGA").unwrap();
+        assert_eq!(hamming_dist_nany(&alpha, &short), 0);
+        assert_eq!(
+            hamming_dist_nany_checked(&alpha, &short),
+            Err(BaseBitsError::LengthMismatch {
+                expected: 5,
+                found: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_count_positions_where() {
+        let alpha = BaseBits::new(b"AGTGCN").unwrap();
+        let beta = BaseBits::new(b"GGCACA").unwrap();
+        let is_purine = |base| base == b'A' || base == b'G';
+        assert_eq!(
+            count_positions_where(&alpha, &beta, |a, b| is_purine(a) && is_purine(b)),
+            3
+        );
+        // Literal agreement, with the N compared as a character
+        assert_eq!(count_positions_where(&alpha, &beta, |a, b| a == b), 2);
+        assert_eq!(count_positions_where(&alpha, &beta, |a, _| a == b'N'), 1);
+        assert_eq!(count_positions_where(&alpha, &beta, |_, _| true), 6);
+    }
+
+    #[test]
+    fn test_within_dist_none() {
+        let alpha = BaseBits::new(b"ACTGACTG").unwrap();
+        let beta = BaseBits::new(b"ACTGANNN").unwrap();
+        // The floor from beta's three N's is already over the threshold
+        assert_eq!(hamming_dist_none(&alpha, &beta), 3);
+        assert!(!within_dist_none(&alpha, &beta, 2));
+        assert!(within_dist_none(&alpha, &beta, 3));
+
+        // Under the floor, but the called bases push it over
+        let beta = BaseBits::new(b"TTTGACTN").unwrap();
+        assert_eq!(hamming_dist_none(&alpha, &beta), 3);
+        assert!(!within_dist_none(&alpha, &beta, 2));
+        assert!(within_dist_none(&alpha, &beta, 3));
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut seqs: Vec<BaseBits> = [&b"TTTT"[..], b"AAAA", b"ACTG"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        seqs.sort();
+        let sorted: Vec<Vec<u8>> = seqs.iter().map(|bb| bb.decode()).collect();
+        assert_eq!(
+            sorted,
+            vec![b"AAAA".to_vec(), b"ACTG".to_vec(), b"TTTT".to_vec()]
+        );
+
+        // Matches sorting the decoded bytes, including N's and prefixes
+        let strs: Vec<&[u8]> = vec![b"GA", b"C", b"", b"NAC", b"A", b"AA", b"ANT", b"TC", b"CG"];
+        let mut seqs: Vec<BaseBits> = strs.iter().map(|s| BaseBits::new(s).unwrap()).collect();
+        seqs.sort();
+        let mut expected = strs.clone();
+        expected.sort();
+        let sorted: Vec<Vec<u8>> = seqs.iter().map(|bb| bb.decode()).collect();
+        let expected: Vec<Vec<u8>> = expected.iter().map(|s| s.to_vec()).collect();
+        assert_eq!(sorted, expected);
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(BaseBits::new(b"GGGG").unwrap(), 1);
+        map.insert(BaseBits::new(b"CCCC").unwrap(), 2);
+        assert_eq!(map.keys().next().unwrap().decode(), b"CCCC".to_vec());
+    }
+
+    #[test]
+    fn test_circular_dist_nany() {
+        let fixed = BaseBits::new(b"ACTGGTCA").unwrap();
+        // fixed rotated by 4
+        let circular = BaseBits::new(b"GTCAACTG").unwrap();
+        assert_eq!(circular.rotated(4), fixed);
+        assert_eq!(circular_dist_nany(&fixed, &circular), (4, 0));
+        assert_eq!(circular_dist_nany(&fixed, &fixed), (0, 0));
+
+        // N's rotate along with their bases
+        let circular = BaseBits::new(b"NAACTGGT").unwrap();
+        let rotated = circular.rotated(2);
+        assert_eq!(rotated.decode(), b"ACTGGTNA".to_vec());
+        assert!(rotated.is_valid());
+        assert_eq!(circular_dist_nany(&fixed, &circular), (2, 0));
+
+        let full = BaseBits::new(&b"ACTGGTCAACTGGTCAACTGG"[..]).unwrap();
+        assert_eq!(full.len(), MAX_BASES);
+        assert_eq!(
+            circular_dist_nany(&full, &full.rotated(5)),
+            (MAX_BASES - 5, 0)
+        );
+        assert!(full.rotated(5).is_valid());
+
+        let empty = BaseBits::new(b"").unwrap();
+        assert_eq!(circular_dist_nany(&empty, &empty), (0, 0));
+    }
+
+    #[test]
+    fn test_as_u64_pair() {
+        let bb = BaseBits::new(b"ACTGN").unwrap();
+        assert_eq!(bb.as_u64_pair(), [bb.code, bb.nbits]);
+        let other = BaseBits::new(b"ACTTA").unwrap();
+        let ([code, nbits], [other_code, other_nbits]) = (bb.as_u64_pair(), other.as_u64_pair());
+        assert_eq!(
+            dist_nany_raw((code, nbits), (other_code, other_nbits)),
+            hamming_dist_nany(&bb, &other)
+        );
+
+        // Field offsets by address, offset_of! would need a newer compiler than the crate supports
+        let base = &bb as *const BaseBits as usize;
+        assert_eq!(&bb.code as *const u64 as usize - base, 0);
+        assert_eq!(&bb.nbits as *const u64 as usize - base, 8);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_entropy_weighted_dist() {
+        // The first position never varies, the last varies across all four bases
+        let refs: Vec<BaseBits> = [&b"AAA"[..], b"ACC", b"AAG", b"ACT"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let entropy = profile_entropy(&profile(&refs));
+        assert_eq!(entropy, vec![0.0, 1.0, 2.0]);
+
+        let query = BaseBits::new(b"AAA").unwrap();
+        let low = BaseBits::new(b"CAA").unwrap();
+        let high = BaseBits::new(b"AAC").unwrap();
+        assert_eq!(
+            hamming_dist_nany(&query, &low),
+            hamming_dist_nany(&query, &high)
+        );
+        assert!(
+            entropy_weighted_dist(&query, &high, &entropy)
+                > entropy_weighted_dist(&query, &low, &entropy)
+        );
+        assert_eq!(entropy_weighted_dist(&query, &high, &entropy), 2.0);
+        assert_eq!(
+            entropy_weighted_dist(&query, &BaseBits::new(b"NCN").unwrap(), &entropy),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_hamming_dist_nany_batch() {
+        let query = BaseBits::new(b"ACTGN").unwrap();
+        let refs: Vec<BaseBits> = [&b"ACTGA"[..], b"ACTTT", b"GGGGG", b"NCTAC", b"TTTTT"]
+            .iter()
+            .map(|s| BaseBits::new(s).unwrap())
+            .collect();
+        let naive: Vec<u32> = refs.iter().map(|r| hamming_dist_nany(&query, r)).collect();
+        assert_eq!(hamming_dist_nany_batch(&query, &refs), naive);
+
+        let mut out = vec![99; refs.len()];
+        hamming_dist_nany_batch_into(&query, &refs, &mut out);
+        assert_eq!(out, naive);
+        assert!(hamming_dist_nany_batch(&query, &[]).is_empty());
+    }
+
     #[test]
     fn test_encoding() {
         let bb = BaseBits::new(b"ACTG").unwrap();
@@ -0,0 +1,119 @@
+//! A 2 bit per base encoding for sequences without N's, holding up to 32 bases in a u64.
+use crate::BaseBitsError;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{fmt, str};
+
+pub const PACKED_ENCODING_LENGTH: u32 = 2;
+pub const MAX_PACKED_BASES: usize = (64 / PACKED_ENCODING_LENGTH) as usize;
+
+/// The low bit of every base
+const LOW_BITS: u64 = 0x5555_5555_5555_5555;
+
+/// A sequence of A, C, G, and T packed 2 bits per base. There is no room for N's, so
+/// `PackedBits::new` rejects them.
+#[derive(Hash, PartialEq, Eq, Debug, Copy, Clone)]
+pub struct PackedBits {
+    /// The u64 holding the encoding
+    pub code: u64,
+    /// The length of the original input
+    len: usize,
+}
+
+impl PackedBits {
+    /// Create a new PackedBits object. Errors if the sequence is longer than
+    /// `MAX_PACKED_BASES` or holds anything other than A, C, G, or T.
+    pub fn new(seq: &[u8]) -> Result<PackedBits, BaseBitsError> {
+        let len = seq.len();
+        if len > MAX_PACKED_BASES {
+            return Err(BaseBitsError::TooLong {
+                len,
+                max: MAX_PACKED_BASES,
+            });
+        }
+        let mut code: u64 = 0;
+        for (pos, &c) in seq.iter().enumerate() {
+            let base = match c {
+                b'A' => 0b00,
+                b'C' => 0b01,
+                b'G' => 0b10,
+                b'T' => 0b11,
+                _ => return Err(BaseBitsError::InvalidBase { pos, base: c }),
+            };
+            code = (code << PACKED_ENCODING_LENGTH) | base;
+        }
+        Ok(PackedBits { code, len })
+    }
+
+    /// The number of bases encoded
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no bases are encoded
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decode a PackedBits object into a string
+    pub fn decode(&self) -> Vec<u8> {
+        (0..self.len)
+            .rev()
+            .map(|i| b"ACGT"[((self.code >> (i as u32 * PACKED_ENCODING_LENGTH)) & 0b11) as usize])
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for PackedBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", str::from_utf8(&self.decode()).unwrap())
+    }
+}
+
+/// Compute the hamming distance between two PackedBits, the number of 2 bit groups that differ
+#[inline]
+pub fn hamming_dist_packed(alpha: &PackedBits, beta: &PackedBits) -> u32 {
+    let diff = alpha.code ^ beta.code;
+    ((diff | (diff >> 1)) & LOW_BITS).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_32bp() {
+        let seq = b"ACTGACTGGATTACAGCCGTAGGCTTAACGTA";
+        assert_eq!(seq.len(), MAX_PACKED_BASES);
+        let alpha = PackedBits::new(seq).unwrap();
+        assert_eq!(alpha.decode(), seq.to_vec());
+        #[cfg(feature = "std")]
+        assert_eq!(alpha.to_string(), str::from_utf8(seq).unwrap());
+
+        let mut other = seq.to_vec();
+        other[0] = b'T';
+        let beta = PackedBits::new(&other).unwrap();
+        assert_eq!(hamming_dist_packed(&alpha, &beta), 1);
+        assert_eq!(hamming_dist_packed(&alpha, &alpha), 0);
+
+        // Every kind of substitution counts once
+        let gc = PackedBits::new(b"ACGT").unwrap();
+        let cat = PackedBits::new(b"CATG").unwrap();
+        assert_eq!(hamming_dist_packed(&gc, &cat), 4);
+    }
+
+    #[test]
+    fn test_packed_errors() {
+        assert_eq!(
+            PackedBits::new(b"ACNT"),
+            Err(BaseBitsError::InvalidBase { pos: 2, base: b'N' })
+        );
+        assert!(PackedBits::new(&[b'A'; MAX_PACKED_BASES + 1]).is_err());
+        #[cfg(feature = "std")]
+        assert_eq!(
+            PackedBits::new(b"AC-T").unwrap_err().to_string(),
+            "Unsupported base '-' at position 2"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Reusable buffers for running allocating operations in tight loops.
+use crate::BaseBits;
+use alloc::vec::Vec;
+
+/// Holds buffers for `BaseBits` operations that would otherwise allocate a new `Vec` each call.
+/// Each method fills its buffer and lends it out until the next call, so after the first few
+/// calls have grown the buffers to size a loop runs without touching the allocator.
+///
+/// ```
+/// use basebits::{BaseBits, ScratchPool};
+///
+/// let mut pool = ScratchPool::new();
+/// for seq in &[&b"ACTG"[..], b"ACNG"] {
+///     let bb = BaseBits::new(seq).unwrap();
+///     assert_eq!(pool.decode(&bb), *seq);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScratchPool {
+    decoded: Vec<u8>,
+    neighbors: Vec<BaseBits>,
+}
+
+impl ScratchPool {
+    /// Create a pool with empty buffers, which grow on first use
+    pub fn new() -> ScratchPool {
+        ScratchPool::default()
+    }
+
+    /// Decode `bb` into the pool's decode buffer, as `BaseBits::decode` would
+    pub fn decode(&mut self, bb: &BaseBits) -> &[u8] {
+        bb.decode_into(&mut self.decoded);
+        &self.decoded
+    }
+
+    /// Collect the single substitution neighbors of `bb` into the pool's neighbor buffer, in the
+    /// order `BaseBits::gray_ordered_ball_1` yields them
+    pub fn neighbors(&mut self, bb: &BaseBits) -> &[BaseBits] {
+        self.neighbors.clear();
+        self.neighbors.extend(bb.gray_ordered_ball_1());
+        &self.neighbors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_pool_reuses_buffers() {
+        let mut pool = ScratchPool::new();
+        let seqs = [&b"ACTGACTGAC"[..], b"GGNTACCATT", b"TTTTAAAACC"];
+
+        let mut decode_ptr = None;
+        let mut neighbor_ptr = None;
+        for _ in 0..3 {
+            for seq in &seqs {
+                let bb = BaseBits::new(seq).unwrap();
+                let decoded = pool.decode(&bb);
+                assert_eq!(decoded, *seq);
+                assert_eq!(
+                    *decode_ptr.get_or_insert(decoded.as_ptr()),
+                    decoded.as_ptr()
+                );
+
+                let expected: Vec<BaseBits> = bb.gray_ordered_ball_1().collect();
+                let neighbors = pool.neighbors(&bb);
+                assert_eq!(neighbors, &expected[..]);
+                // The first sequence has the most neighbors, so the buffer never needs to grow
+                assert_eq!(
+                    *neighbor_ptr.get_or_insert(neighbors.as_ptr()),
+                    neighbors.as_ptr()
+                );
+            }
+        }
+    }
+}
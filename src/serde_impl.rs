@@ -0,0 +1,70 @@
+//! `Serialize` and `Deserialize` for `BaseBits`, behind the `serde` feature. The encoding is
+//! stored as its `code`, `nbits` and `len` fields, and checked with `BaseBits::is_valid` when read
+//! back so a corrupt cache can't produce a `BaseBits` that `new` never would.
+use crate::BaseBits;
+use serde::de::Error;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for BaseBits {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("BaseBits", 3)?;
+        state.serialize_field("code", &self.code)?;
+        state.serialize_field("nbits", &self.nbits)?;
+        state.serialize_field("len", &self.len)?;
+        state.end()
+    }
+}
+
+/// The unchecked fields, as they were serialized
+#[derive(Deserialize)]
+#[serde(rename = "BaseBits")]
+struct RawBaseBits {
+    code: u64,
+    nbits: u64,
+    len: usize,
+}
+
+impl<'de> Deserialize<'de> for BaseBits {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawBaseBits::deserialize(deserializer)?;
+        let bb = BaseBits {
+            code: raw.code,
+            nbits: raw.nbits,
+            len: raw.len,
+        };
+        if bb.is_valid() {
+            Ok(bb)
+        } else {
+            Err(D::Error::custom(format!(
+                "inconsistent BaseBits encoding: code {:#x}, nbits {:#x}, len {}",
+                raw.code, raw.nbits, raw.len
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_BASES;
+
+    #[test]
+    fn test_serde_json_round_trip() {
+        let bb = BaseBits::new(b"ACTGN").unwrap();
+        let json = serde_json::to_string(&bb).unwrap();
+        let back: BaseBits = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, bb);
+        assert_eq!(back.decode(), b"ACTGN".to_vec());
+    }
+
+    #[test]
+    fn test_serde_rejects_invalid() {
+        let too_long = format!(r#"{{"code":0,"nbits":0,"len":{}}}"#, MAX_BASES + 1);
+        assert!(serde_json::from_str::<BaseBits>(&too_long).is_err());
+
+        // nbits claims no N's but the high bits above len are clear
+        let bad_nbits = r#"{"code":0,"nbits":63,"len":2}"#;
+        assert!(serde_json::from_str::<BaseBits>(bad_nbits).is_err());
+    }
+}
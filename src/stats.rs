@@ -0,0 +1,83 @@
+//! Running summary statistics over streams of barcodes.
+use crate::BaseBits;
+
+/// Running GC content statistics, updated one barcode at a time
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct GcStats {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl GcStats {
+    /// Create an empty GcStats
+    pub fn new() -> GcStats {
+        GcStats::default()
+    }
+
+    /// Add the GC fraction of `bb` to the running statistics
+    pub fn observe(&mut self, bb: &BaseBits) {
+        let gc = bb.gc_fraction();
+        if self.count == 0 {
+            self.min = gc;
+            self.max = gc;
+        } else {
+            self.min = self.min.min(gc);
+            self.max = self.max.max(gc);
+        }
+        self.count += 1;
+        self.sum += gc;
+    }
+
+    /// The number of barcodes observed
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The mean GC fraction, or `None` if nothing has been observed
+    pub fn mean(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            count => Some(self.sum / count as f64),
+        }
+    }
+
+    /// The lowest GC fraction seen, or `None` if nothing has been observed
+    pub fn min(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            _ => Some(self.min),
+        }
+    }
+
+    /// The highest GC fraction seen, or `None` if nothing has been observed
+    pub fn max(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            _ => Some(self.max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gc_stats() {
+        let mut stats = GcStats::new();
+        assert_eq!(stats.mean(), None);
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.max(), None);
+
+        for seq in &[&b"GCGC"[..], b"ATAT", b"ACTG", b"GCAN"] {
+            stats.observe(&BaseBits::new(seq).unwrap());
+        }
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.min(), Some(0.0));
+        assert_eq!(stats.max(), Some(1.0));
+        let expected = (1.0 + 0.0 + 0.5 + 2.0 / 3.0) / 4.0;
+        assert!((stats.mean().unwrap() - expected).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,83 @@
+//! Policies for choosing between equally good candidates in argmin style scans.
+
+/// How to resolve a tie between candidates that are equally good
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    /// Keep the candidate that comes first
+    #[default]
+    LowestIndex,
+    /// Keep the candidate that comes last
+    HighestIndex,
+    /// Pick uniformly at random among the tied candidates, reproducibly for a given seed
+    Random(u64),
+}
+
+/// Applies a `TieBreak` over a scan, one candidate at a time. Random ties are resolved with
+/// reservoir sampling, so every tied candidate is equally likely to be kept without collecting
+/// them first.
+pub(crate) struct TieBreaker {
+    policy: TieBreak,
+    state: u64,
+    ties: u64,
+}
+
+impl TieBreaker {
+    pub(crate) fn new(policy: TieBreak) -> Self {
+        let state = match policy {
+            TieBreak::Random(seed) => seed,
+            _ => 0,
+        };
+        TieBreaker {
+            policy,
+            state,
+            ties: 0,
+        }
+    }
+
+    /// Record a new best candidate that strictly beats the previous one
+    pub(crate) fn reset(&mut self) {
+        self.ties = 1;
+    }
+
+    /// Record a candidate tied with the current best, returning whether it should replace it
+    pub(crate) fn replace_on_tie(&mut self) -> bool {
+        self.ties += 1;
+        match self.policy {
+            TieBreak::LowestIndex => false,
+            TieBreak::HighestIndex => true,
+            TieBreak::Random(_) => self.next_u64() % self.ties == 0,
+        }
+    }
+
+    /// splitmix64
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        crate::mix64(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_tie_break_is_uniform_and_seeded() {
+        let pick = |seed| {
+            let mut breaker = TieBreaker::new(TieBreak::Random(seed));
+            breaker.reset();
+            let mut kept = 0;
+            for i in 1..4 {
+                if breaker.replace_on_tie() {
+                    kept = i;
+                }
+            }
+            kept
+        };
+        let mut seen = [0; 4];
+        for seed in 0..400 {
+            seen[pick(seed)] += 1;
+        }
+        assert!(seen.iter().all(|&n| n > 50), "{:?}", seen);
+        assert_eq!(pick(7), pick(7));
+    }
+}